@@ -1,20 +1,40 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use tauri::command;
 use crate::engine;
 use crate::models::*;
-use crate::commands::library;
+use crate::commands::{library, runtime, settings};
 
 #[command]
 pub fn analyze_seed(prompt: String) -> Result<SeedAnalysis, String> {
     Ok(engine::bootstrap::analyze_seed(&prompt))
 }
 
+/// Analyze the seed prompt with the configured provider, falling back to the
+/// keyword analyzer if the call fails or the answer can't be parsed.
 #[command]
-pub fn bootstrap(prompt: String, output_dir: String) -> Result<FactoryConfig, String> {
-    let config = engine::bootstrap::build_config(&prompt);
+pub fn analyze_seed_llm(prompt: String, engine: String, model: String) -> Result<SeedAnalysis, String> {
+    let analysis = runtime::complete_once(&engine, &model, engine::bootstrap::SEED_ANALYSIS_PROMPT, &prompt)
+        .ok()
+        .and_then(|text| engine::bootstrap::parse_seed_analysis(&text));
+    Ok(analysis.unwrap_or_else(|| engine::bootstrap::analyze_seed(&prompt)))
+}
 
-    // Save config to output dir
+/// Build the config `bootstrap` would generate, without writing anything.
+#[command]
+pub fn preview_bootstrap(prompt: String) -> Result<FactoryConfig, String> {
+    Ok(build_config_from_settings(&prompt))
+}
+
+/// Generate a new project in `output_dir`. Fails if the directory already
+/// holds a project unless `overwrite` is set.
+#[command]
+pub fn bootstrap(prompt: String, output_dir: String, overwrite: Option<bool>) -> Result<FactoryConfig, String> {
     let dir = PathBuf::from(&output_dir);
+    validate_output_dir(&dir, overwrite.unwrap_or(false))?;
+
+    let config = build_config_from_settings(&prompt);
+
+    // Save config to output dir
     std::fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create output dir: {}", e))?;
 
@@ -25,7 +45,7 @@ pub fn bootstrap(prompt: String, output_dir: String) -> Result<FactoryConfig, St
 
     // Auto-generate all project files immediately
     let templates_dir = dir.join("templates");
-    engine::generator::generate_all(&config, &dir, &templates_dir)?;
+    engine::generator::generate_all(&config, &dir, &templates_dir, GenerateMode::Overwrite)?;
 
     // Register project in the global registry so Dashboard can find it
     library::register_project(&config.company.name, &output_dir)?;
@@ -33,8 +53,57 @@ pub fn bootstrap(prompt: String, output_dir: String) -> Result<FactoryConfig, St
     Ok(config)
 }
 
+/// Reject output directories that would clobber an existing project or land
+/// somewhere no project belongs (filesystem roots, home, the app's own data).
+fn validate_output_dir(dir: &Path, overwrite: bool) -> Result<(), String> {
+    if dir.as_os_str().is_empty() {
+        return Err("Output directory is required".to_string());
+    }
+    if !dir.is_absolute() {
+        return Err(format!("Output directory must be an absolute path: {}", dir.display()));
+    }
+    if dir.components().any(|c| matches!(c, Component::ParentDir)) {
+        return Err(format!("Output directory must not contain '..': {}", dir.display()));
+    }
+    if dir.parent().is_none() {
+        return Err(format!("Refusing to generate a project at a filesystem root: {}", dir.display()));
+    }
+
+    let protected = [dirs::home_dir(), dirs::data_dir(), dirs::config_dir(), dirs::document_dir(), dirs::desktop_dir()];
+    if protected.iter().flatten().any(|p| p == dir) {
+        return Err(format!("Refusing to generate a project directly in {}", dir.display()));
+    }
+
+    // The app's data dir is off limits except for its projects folder
+    if let Some(data_dir) = dirs::data_dir().map(|d| d.join("omnihive")) {
+        if dir.starts_with(&data_dir) && !dir.starts_with(data_dir.join("projects")) {
+            return Err(format!("Refusing to generate a project inside the app data directory: {}", dir.display()));
+        }
+        if dir == data_dir.join("projects") {
+            return Err("Choose a folder inside the projects directory, not the directory itself".to_string());
+        }
+    }
+
+    if !overwrite && dir.join("company.yaml").exists() {
+        return Err(format!(
+            "A project already exists at {}. Choose another folder or enable overwrite.",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+fn build_config_from_settings(prompt: &str) -> FactoryConfig {
+    let persona_overrides = settings::load_settings()
+        .map(|s| s.role_persona_overrides)
+        .unwrap_or_default();
+    engine::bootstrap::build_config(prompt, &persona_overrides)
+}
+
+/// Regenerate project files from a company.yaml. `preserve` mode keeps any
+/// existing file whose content differs from what would be generated.
 #[command]
-pub fn generate(config_path: String) -> Result<GenerateResult, String> {
+pub fn generate(config_path: String, mode: Option<GenerateMode>) -> Result<GenerateResult, String> {
     let path = PathBuf::from(&config_path);
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read config: {}", e))?;
@@ -45,12 +114,61 @@ pub fn generate(config_path: String) -> Result<GenerateResult, String> {
     let output_dir = path.parent().unwrap_or(&fallback);
     let templates_dir = output_dir.join("templates");
 
-    engine::generator::generate_all(&config, output_dir, &templates_dir)
+    engine::generator::generate_all(&config, output_dir, &templates_dir, mode.unwrap_or_default())
+}
+
+/// Recreate a project's missing directories and core files from its
+/// company.yaml without touching anything that exists. Returns what was repaired.
+#[command]
+pub fn repair_project(project_dir: String) -> Result<Vec<String>, String> {
+    let dir = PathBuf::from(&project_dir);
+    let content = std::fs::read_to_string(dir.join("company.yaml"))
+        .map_err(|e| format!("Failed to read company.yaml: {}", e))?;
+    let config: FactoryConfig = serde_yaml::from_str(&content)
+        .map_err(|e| format!("YAML parse error: {}", e))?;
+    engine::generator::repair_project(&config, &dir)
 }
 
 #[command]
 pub fn validate_config(config: FactoryConfig) -> Vec<String> {
-    engine::guardrails::validate_config_guardrails(&config.guardrails)
+    let mut warnings = engine::guardrails::validate_config_guardrails(&config.guardrails);
+    warnings.extend(engine::guardrails::validate_config_workflows(&config));
+    warnings
+}
+
+/// Read and check a company.yaml on disk, reporting parse failures with the
+/// offending line.
+#[command]
+pub fn validate_config_file(path: String) -> ConfigValidation {
+    let invalid = |error: String| ConfigValidation { valid: false, errors: vec![error], warnings: vec![] };
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) => return invalid(format!("Failed to read config: {}", e)),
+    };
+    let config: FactoryConfig = match serde_yaml::from_str(&content) {
+        Ok(c) => c,
+        Err(e) => return invalid(yaml_error_with_context(&e, &content)),
+    };
+
+    ConfigValidation { valid: true, errors: vec![], warnings: validate_config(config) }
+}
+
+fn yaml_error_with_context(error: &serde_yaml::Error, content: &str) -> String {
+    match error.location() {
+        Some(loc) => {
+            let line = content.lines().nth(loc.line().saturating_sub(1)).unwrap_or_default();
+            format!(
+                "YAML parse error at line {}, column {}: {}\n{:>4} | {}",
+                loc.line(),
+                loc.column(),
+                error,
+                loc.line(),
+                line
+            )
+        }
+        None => format!("YAML parse error: {}", error),
+    }
 }
 
 #[command]
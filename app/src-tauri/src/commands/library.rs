@@ -50,6 +50,8 @@ pub fn register_project(name: &str, output_dir: &str) -> Result<(), String> {
         name: name.to_string(),
         output_dir: output_dir.to_string(),
         created_at: chrono::Local::now().format("%+").to_string(),
+        archived: false,
+        archived_from: String::new(),
     });
 
     save_registry(&registry)
@@ -62,10 +64,21 @@ pub fn get_library_dir_pub() -> Option<PathBuf> {
     get_library_dir()
 }
 
-/// Resolve the library directory. Checks for a local `library/` folder next to
-/// the executable first, then falls back to a `library/` relative to CWD or
-/// the omnihive source root.
+/// Resolve the library directory. Uses `library_dir` from settings when it is
+/// set and exists, then checks for a local `library/` folder next to the
+/// executable, then falls back to a `library/` relative to CWD or the
+/// omnihive source root.
 fn get_library_dir() -> Option<PathBuf> {
+    // Configured in settings
+    if let Ok(settings) = crate::commands::settings::load_settings() {
+        if !settings.library_dir.trim().is_empty() {
+            let configured = PathBuf::from(settings.library_dir.trim());
+            if configured.exists() {
+                return Some(configured);
+            }
+        }
+    }
+
     // Check relative to executable
     if let Ok(exe) = std::env::current_exe() {
         if let Some(parent) = exe.parent() {
@@ -388,7 +401,7 @@ fn truncate(s: &str, max: usize) -> String {
 
 // ===== Workflow loading =====
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 struct WorkflowYaml {
     id: String,
     name: String,
@@ -400,10 +413,10 @@ struct WorkflowYaml {
     convergence_cycles: u32,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 struct WorkflowStepYaml {
     role: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     persona: String,
 }
 
@@ -454,56 +467,209 @@ fn load_workflows_from_files() -> Option<Vec<WorkflowInfo>> {
 
 #[command]
 pub fn list_personas() -> Result<Vec<PersonaInfo>, String> {
-    if let Some(personas) = load_personas_from_files() {
-        return Ok(personas);
-    }
     // Fallback to hardcoded defaults
-    Ok(fallback_personas())
+    let mut personas = load_personas_from_files().unwrap_or_else(fallback_personas);
+    let state = load_library_state();
+    for p in &mut personas {
+        p.enabled = !state.disabled_personas.contains(&p.id);
+    }
+    Ok(personas)
 }
 
 #[command]
 pub fn list_skills() -> Result<Vec<SkillInfo>, String> {
-    if let Some(skills) = load_skills_from_files() {
-        return Ok(skills);
+    let mut skills = load_skills_from_files().unwrap_or_else(fallback_skills);
+    let state = load_library_state();
+    for s in &mut skills {
+        s.enabled = !state.disabled_skills.contains(&s.id);
     }
-    Ok(fallback_skills())
+    Ok(skills)
 }
 
 #[command]
 pub fn list_workflows() -> Result<Vec<WorkflowInfo>, String> {
-    if let Some(workflows) = load_workflows_from_files() {
-        return Ok(workflows);
+    let mut workflows = load_workflows_from_files().unwrap_or_else(fallback_workflows);
+    let state = load_library_state();
+    for w in &mut workflows {
+        w.enabled = !state.disabled_workflows.contains(&w.id);
     }
-    Ok(fallback_workflows())
+    Ok(workflows)
 }
 
+/// Full text of a skill: the YAML definition or SKILL.md body. Library
+/// locations are searched in the same order the runtime uses
+/// (skills/, real-skills/, ecc-skills/), then user-created custom skills.
+/// `custom:` ids go straight to the custom skills folder.
 #[command]
 pub fn get_skill_content(skill_id: String) -> Result<String, String> {
+    let slug = skill_id.strip_prefix("custom:").unwrap_or(&skill_id);
+    if slug.is_empty() || slug.contains(['/', '\\']) || slug.contains("..") {
+        return Err(format!("Invalid skill id: {}", skill_id));
+    }
+
+    let mut candidates = Vec::new();
+    if !skill_id.starts_with("custom:") {
+        if let Some(lib_dir) = get_library_dir() {
+            candidates.push(lib_dir.join("skills").join(format!("{}.yaml", slug)));
+            candidates.push(lib_dir.join("real-skills").join(slug).join("SKILL.md"));
+            candidates.push(lib_dir.join("ecc-skills").join(slug).join("SKILL.md"));
+        }
+    }
+    candidates.push(crate::commands::skill_manager::get_custom_skills_dir().join(slug).join("SKILL.md"));
+
+    match candidates.iter().find(|p| p.exists()) {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("Failed to read skill: {}", e)),
+        None => Err(format!("Skill '{}' not found", skill_id)),
+    }
+}
+
+/// Create or overwrite `library/workflows/{id}.yaml`. Every role in the chain
+/// must belong to a known persona (library or custom).
+#[command]
+pub fn save_workflow(workflow: WorkflowInfo) -> Result<WorkflowInfo, String> {
+    let id = workflow.id.trim();
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Invalid workflow id: '{}'", workflow.id));
+    }
+    if workflow.chain.is_empty() {
+        return Err("Workflow chain cannot be empty".to_string());
+    }
+
+    let mut personas = list_personas()?;
+    personas.extend(crate::commands::skill_manager::list_custom_agents().unwrap_or_default());
+    let unknown: Vec<&str> = workflow
+        .chain
+        .iter()
+        .filter(|role| !personas.iter().any(|p| &p.role == *role))
+        .map(|r| r.as_str())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("Unknown roles in workflow chain: {}", unknown.join(", ")));
+    }
+
+    let lib_dir = get_library_dir()
+        .ok_or_else(|| "Library directory not found".to_string())?;
+    let workflows_dir = lib_dir.join("workflows");
+    std::fs::create_dir_all(&workflows_dir)
+        .map_err(|e| format!("Failed to create workflows dir: {}", e))?;
+
+    let yaml = WorkflowYaml {
+        id: id.to_string(),
+        name: workflow.name.clone(),
+        description: workflow.description.clone(),
+        chain: workflow
+            .chain
+            .iter()
+            .map(|role| WorkflowStepYaml {
+                role: role.clone(),
+                persona: personas
+                    .iter()
+                    .find(|p| &p.role == role)
+                    .map(|p| p.id.clone())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        convergence_cycles: workflow.convergence_cycles.max(1),
+    };
+    let content = serde_yaml::to_string(&yaml)
+        .map_err(|e| format!("YAML serialize error: {}", e))?;
+    let path = workflows_dir.join(format!("{}.yaml", id));
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write workflow: {}", e))?;
+
+    Ok(WorkflowInfo {
+        id: yaml.id,
+        convergence_cycles: yaml.convergence_cycles,
+        file_path: Some(path.display().to_string()),
+        ..workflow
+    })
+}
+
+#[command]
+pub fn delete_workflow(id: String) -> Result<bool, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("Invalid workflow id: '{}'", id));
+    }
     let lib_dir = get_library_dir()
         .ok_or_else(|| "Library directory not found".to_string())?;
+    let path = lib_dir.join("workflows").join(format!("{}.yaml", id));
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path)
+        .map_err(|e| format!("Failed to delete workflow: {}", e))?;
+    Ok(true)
+}
 
-    // Try real-skills/{id}/SKILL.md
-    let real_path = lib_dir.join("real-skills").join(&skill_id).join("SKILL.md");
-    if real_path.exists() {
-        return std::fs::read_to_string(&real_path)
-            .map_err(|e| format!("Failed to read skill: {}", e));
+/// Case-insensitive search across personas, skills and workflows (library and
+/// custom). Name matches rank above tag/capability matches, which rank above
+/// description matches.
+#[command]
+pub fn search_library(query: String) -> Result<Vec<LibrarySearchResult>, String> {
+    use crate::commands::skill_manager;
+
+    let q = query.trim().to_lowercase();
+    if q.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut results = Vec::new();
+    let mut push = |kind: &str, id: &str, name: &str, description: &str, score: u32| {
+        if score > 0 && !results.iter().any(|r: &LibrarySearchResult| r.kind == kind && r.id == id) {
+            results.push(LibrarySearchResult {
+                kind: kind.to_string(),
+                id: id.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+                score,
+            });
+        }
+    };
+
+    let mut personas = list_personas()?;
+    personas.extend(skill_manager::list_custom_agents().unwrap_or_default());
+    for p in &personas {
+        let extra: Vec<&String> = p.tags.iter().chain(&p.core_capabilities).chain(&p.mental_models).collect();
+        let score = search_score(&q, &p.name, &p.id, &extra, &[&p.expertise, &p.role]);
+        push("persona", &p.id, &p.name, &p.expertise, score);
     }
 
-    // Try ecc-skills/{id}/SKILL.md
-    let ecc_path = lib_dir.join("ecc-skills").join(&skill_id).join("SKILL.md");
-    if ecc_path.exists() {
-        return std::fs::read_to_string(&ecc_path)
-            .map_err(|e| format!("Failed to read skill: {}", e));
+    let mut skills = list_skills()?;
+    skills.extend(skill_manager::list_custom_skills().unwrap_or_default());
+    for sk in &skills {
+        let extra: Vec<&String> = sk.tags.iter().collect();
+        let score = search_score(&q, &sk.name, &sk.id, &extra, &[&sk.description, &sk.category]);
+        push("skill", &sk.id, &sk.name, &sk.description, score);
     }
 
-    // Try skills/{id}.yaml
-    let yaml_path = lib_dir.join("skills").join(format!("{}.yaml", skill_id));
-    if yaml_path.exists() {
-        return std::fs::read_to_string(&yaml_path)
-            .map_err(|e| format!("Failed to read skill: {}", e));
+    let mut workflows = list_workflows()?;
+    workflows.extend(skill_manager::list_custom_workflows().unwrap_or_default());
+    for w in &workflows {
+        let extra: Vec<&String> = w.tags.iter().chain(&w.chain).collect();
+        let score = search_score(&q, &w.name, &w.id, &extra, &[&w.description]);
+        push("workflow", &w.id, &w.name, &w.description, score);
     }
 
-    Err(format!("Skill '{}' not found in library", skill_id))
+    results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+    Ok(results)
+}
+
+/// Score a library item against a lowercased query (0 = no match).
+fn search_score(q: &str, name: &str, id: &str, extra: &[&String], descriptions: &[&String]) -> u32 {
+    let name = name.to_lowercase();
+    if name == q || id.to_lowercase() == q {
+        100
+    } else if name.starts_with(q) {
+        80
+    } else if name.contains(q) || id.to_lowercase().contains(q) {
+        60
+    } else if extra.iter().any(|e| e.to_lowercase().contains(q)) {
+        30
+    } else if descriptions.iter().any(|d| d.to_lowercase().contains(q)) {
+        10
+    } else {
+        0
+    }
 }
 
 /// Toggle a library item's enabled state (persisted in library_state.json).
@@ -541,11 +707,100 @@ pub fn toggle_library_item(item_type: String, item_id: String, enabled: bool) ->
     Ok(enabled)
 }
 
+#[command]
+pub fn set_persona_enabled(id: String, enabled: bool) -> Result<bool, String> {
+    toggle_library_item("persona".to_string(), id, enabled)
+}
+
+#[command]
+pub fn set_skill_enabled(id: String, enabled: bool) -> Result<bool, String> {
+    toggle_library_item("skill".to_string(), id, enabled)
+}
+
 #[command]
 pub fn get_library_state() -> Result<LibraryState, String> {
     Ok(load_library_state())
 }
 
+/// Parse every persona, skill and workflow file in the library and report
+/// the ones the loaders would silently skip.
+#[command]
+pub fn validate_library() -> Result<Vec<LibraryValidationIssue>, String> {
+    let lib_dir = get_library_dir().ok_or_else(|| "Library directory not found".to_string())?;
+    let mut issues = Vec::new();
+
+    for (kind, sub) in [("persona", "personas"), ("skill", "skills"), ("workflow", "workflows")] {
+        let parse: fn(&str) -> Result<(), String> = match kind {
+            "persona" => check_yaml::<PersonaYaml>,
+            "skill" => check_yaml::<SkillYaml>,
+            _ => check_yaml::<WorkflowYaml>,
+        };
+        for path in sorted_entries(&lib_dir.join(sub)) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if name.starts_with('_') || !name.ends_with(".yaml") {
+                continue;
+            }
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| parse(&content));
+            if let Err(error) = result {
+                issues.push(LibraryValidationIssue {
+                    kind: kind.to_string(),
+                    file_path: path.display().to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    for sub in ["real-skills", "ecc-skills"] {
+        for dir_path in sorted_entries(&lib_dir.join(sub)) {
+            let skill_md = dir_path.join("SKILL.md");
+            if !skill_md.exists() {
+                continue;
+            }
+            let result = std::fs::read_to_string(&skill_md)
+                .map_err(|e| format!("Failed to read file: {}", e))
+                .and_then(|content| check_skill_md_frontmatter(&content));
+            if let Err(error) = result {
+                issues.push(LibraryValidationIssue {
+                    kind: "skill".to_string(),
+                    file_path: skill_md.display().to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+fn check_yaml<T: serde::de::DeserializeOwned>(content: &str) -> Result<(), String> {
+    serde_yaml::from_str::<T>(content).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn sorted_entries(dir: &std::path::Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// A SKILL.md frontmatter block, when present, must be closed and parse as YAML.
+fn check_skill_md_frontmatter(content: &str) -> Result<(), String> {
+    if !content.starts_with("---") {
+        return Ok(());
+    }
+    let parts: Vec<&str> = content.splitn(3, "---").collect();
+    if parts.len() < 3 {
+        return Err("Frontmatter is missing its closing '---'".to_string());
+    }
+    serde_yaml::from_str::<serde_yaml::Value>(parts[1])
+        .map(|_| ())
+        .map_err(|e| format!("Invalid frontmatter: {}", e))
+}
+
 // ===== Library State Persistence =====
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -678,10 +933,19 @@ fn fallback_workflows() -> Vec<WorkflowInfo> {
 
 #[command]
 pub fn list_projects() -> Result<Vec<Project>, String> {
+    Ok(collect_projects(false))
+}
+
+#[command]
+pub fn list_archived_projects() -> Result<Vec<Project>, String> {
+    Ok(collect_projects(true))
+}
+
+fn collect_projects(archived: bool) -> Vec<Project> {
     let registry = load_registry();
     let mut projects = Vec::new();
 
-    for entry in &registry.projects {
+    for entry in registry.projects.iter().filter(|p| p.archived == archived) {
         let path = PathBuf::from(&entry.output_dir);
         let config_path = path.join("company.yaml");
 
@@ -725,7 +989,7 @@ pub fn list_projects() -> Result<Vec<Project>, String> {
         }
     }
 
-    Ok(projects)
+    projects
 }
 
 #[command]
@@ -756,6 +1020,226 @@ pub fn delete_project(id: String) -> Result<bool, String> {
     }
 }
 
+/// Move a project into `archive/` under the app's projects directory and mark
+/// it archived in the registry. Unlike `delete_project`, nothing is destroyed.
+/// Refused while a loop is running on the project.
+#[command]
+pub fn archive_project(id: String) -> Result<bool, String> {
+    let mut registry = load_registry();
+    let Some(entry) = registry.projects.iter_mut().find(|p| p.id == id && !p.archived) else {
+        return Ok(false);
+    };
+
+    let path = PathBuf::from(&entry.output_dir);
+    if !path.exists() {
+        return Err(format!("Project directory {} does not exist", path.display()));
+    }
+    if crate::commands::runtime::loop_is_active(&path) {
+        return Err("Stop the running loop before archiving this project".to_string());
+    }
+    let name = path.file_name()
+        .ok_or_else(|| "Project directory has no name".to_string())?;
+    let settings = crate::commands::settings::load_settings()?;
+    let archive_dir = PathBuf::from(&settings.projects_dir).join("archive");
+    std::fs::create_dir_all(&archive_dir)
+        .map_err(|e| format!("Failed to create archive dir: {}", e))?;
+
+    let mut target = archive_dir.join(name);
+    if target.exists() {
+        let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        target = archive_dir.join(format!("{}_{}", name.to_string_lossy(), stamp));
+    }
+    move_dir(&path, &target).map_err(|e| format!("Failed to archive project: {}", e))?;
+
+    entry.archived_from = entry.output_dir.clone();
+    entry.output_dir = target.display().to_string();
+    entry.archived = true;
+    save_registry(&registry)?;
+    Ok(true)
+}
+
+/// Rename `from` to `to`, falling back to copy-then-delete when they are on
+/// different filesystems (e.g. a project on another drive).
+fn move_dir(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_dir(from, to)?;
+    std::fs::remove_dir_all(from)
+}
+
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let dest = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &dest)?;
+        } else if file_type.is_file() {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move an archived project back to where it lived before archiving and list
+/// it again.
+#[command]
+pub fn restore_project(id: String) -> Result<bool, String> {
+    let mut registry = load_registry();
+    let Some(entry) = registry.projects.iter_mut().find(|p| p.id == id && p.archived) else {
+        return Ok(false);
+    };
+
+    let path = PathBuf::from(&entry.output_dir);
+    if !path.exists() {
+        return Err(format!("Archived project directory {} does not exist", path.display()));
+    }
+    // Entries archived before `archived_from` was recorded go back beside `archive/`
+    let target = if entry.archived_from.is_empty() {
+        path.parent()
+            .and_then(|archive| archive.parent())
+            .ok_or_else(|| "Archived project has no projects root".to_string())?
+            .join(&entry.id)
+    } else {
+        PathBuf::from(&entry.archived_from)
+    };
+    if target.exists() {
+        return Err(format!("Cannot restore: {} already exists", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    move_dir(&path, &target).map_err(|e| format!("Failed to restore project: {}", e))?;
+
+    entry.output_dir = target.display().to_string();
+    entry.archived_from.clear();
+    entry.archived = false;
+    save_registry(&registry)?;
+    Ok(true)
+}
+
+// ===== Project Export / Import =====
+
+/// Files that mark a loop as running; left out of exports so imports start clean.
+const EXPORT_EXCLUDED: &[&str] = &[".loop.state", ".loop.pid", ".loop.lock"];
+
+/// Directory names skipped anywhere in an export; they are rebuilt from manifests.
+const EXPORT_EXCLUDED_DIRS: &[&str] = &["node_modules"];
+
+/// Zip a project directory into the user's download dir and return the zip path.
+#[command]
+pub fn export_project(id: String) -> Result<String, String> {
+    let registry = load_registry();
+    let entry = registry.projects.iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Project not found: {}", id))?;
+    let project_dir = PathBuf::from(&entry.output_dir);
+    if !project_dir.join("company.yaml").exists() {
+        return Err("Not a valid project directory (missing company.yaml)".to_string());
+    }
+
+    let dest_dir = dirs::download_dir()
+        .or_else(dirs::data_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    std::fs::create_dir_all(&dest_dir)
+        .map_err(|e| format!("Failed to create export dir: {}", e))?;
+    let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    let dest = dest_dir.join(format!("{}_{}.zip", id, stamp));
+
+    let file = std::fs::File::create(&dest)
+        .map_err(|e| format!("Failed to create export file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    add_dir_to_zip(&mut zip, &project_dir, &project_dir)?;
+    zip.finish().map_err(|e| format!("Failed to finalize export: {}", e))?;
+
+    Ok(dest.display().to_string())
+}
+
+fn add_dir_to_zip(zip: &mut zip::ZipWriter<std::fs::File>, root: &std::path::Path, dir: &std::path::Path) -> Result<(), String> {
+    use std::io::Write;
+
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let name = rel.to_string_lossy().replace('\\', "/");
+        // file_type() doesn't follow symlinks, so a link back to an ancestor can't loop
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        if file_type.is_symlink() {
+            continue;
+        } else if file_type.is_dir() {
+            if !EXPORT_EXCLUDED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                add_dir_to_zip(zip, root, &path)?;
+            }
+        } else if !EXPORT_EXCLUDED.contains(&name.as_str()) {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            zip.start_file(name.as_str(), options)
+                .map_err(|e| format!("Failed to add {} to export: {}", name, e))?;
+            zip.write_all(&bytes)
+                .map_err(|e| format!("Failed to write {} to export: {}", name, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Unpack an exported project zip into `output_dir` and register it.
+#[command]
+pub fn import_project(zip_path: String, output_dir: String) -> Result<Project, String> {
+    let dest = PathBuf::from(&output_dir);
+    if dest.exists() && std::fs::read_dir(&dest).map(|mut d| d.next().is_some()).unwrap_or(false) {
+        return Err(format!("Output directory is not empty: {}", output_dir));
+    }
+
+    let file = std::fs::File::open(&zip_path)
+        .map_err(|e| format!("Failed to open {}: {}", zip_path, e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Invalid project zip: {}", e))?;
+
+    for i in 0..archive.len() {
+        let mut item = archive.by_index(i)
+            .map_err(|e| format!("Failed to read zip entry: {}", e))?;
+        // enclosed_name rejects absolute paths and `..` traversal
+        let Some(rel) = item.enclosed_name() else {
+            continue;
+        };
+        let target = dest.join(rel);
+        if item.is_dir() {
+            std::fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = std::fs::File::create(&target)
+            .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        std::io::copy(&mut item, &mut out)
+            .map_err(|e| format!("Failed to extract {}: {}", target.display(), e))?;
+    }
+
+    let content = std::fs::read_to_string(dest.join("company.yaml"))
+        .map_err(|_| "Imported archive has no company.yaml".to_string())?;
+    let config: FactoryConfig = serde_yaml::from_str(&content)
+        .map_err(|e| format!("Failed to parse company.yaml: {}", e))?;
+
+    register_project(&config.company.name, &output_dir)?;
+
+    let id = dest.file_name().unwrap_or_default().to_string_lossy().to_string();
+    get_project(id)
+}
+
 // Helper trait for pipe
 trait Pipe: Sized {
     fn pipe<F, R>(self, f: F) -> R where F: FnOnce(Self) -> R {
@@ -1,6 +1,22 @@
+use std::time::Duration;
 use tauri::command;
+use crate::commands::runtime::{find_binary, silent_command};
+use crate::engine::mcp_client;
 use crate::models::*;
 
+/// Env values with this prefix name an OS keyring entry instead of holding
+/// the secret itself, e.g. `keyring:github-token`.
+const KEYRING_PREFIX: &str = "keyring:";
+
+/// Keyring service under which MCP secrets are stored.
+const KEYRING_SERVICE: &str = "omnihive";
+
+/// How long to wait for each MCP request during discovery.
+const MCP_DISCOVERY_TIMEOUT_SECS: u64 = 30;
+
+/// Shorter timeout used when only checking that a server responds.
+const MCP_TEST_TIMEOUT_SECS: u64 = 15;
+
 /// List all configured MCP servers from settings.
 #[command]
 pub fn list_mcp_servers() -> Result<Vec<McpServerConfig>, String> {
@@ -47,6 +63,162 @@ pub fn remove_mcp_server(server_id: String) -> Result<AppSettings, String> {
     Ok(settings)
 }
 
+/// Connect to a configured MCP server, list its tools and cache them in settings.
+#[command]
+pub fn discover_mcp_tools(server_id: String) -> Result<Vec<McpToolInfo>, String> {
+    let mut settings = crate::commands::settings::load_settings()?;
+    let server = settings
+        .mcp_servers
+        .iter()
+        .find(|s| s.id == server_id)
+        .cloned()
+        .ok_or_else(|| format!("MCP server '{}' not found", server_id))?;
+
+    let (_, tools) = connect_and_list(&server, Duration::from_secs(MCP_DISCOVERY_TIMEOUT_SECS))?;
+
+    if let Some(existing) = settings.mcp_servers.iter_mut().find(|s| s.id == server_id) {
+        existing.tools = tools.clone();
+    }
+    crate::commands::settings::save_settings(settings)?;
+    Ok(tools)
+}
+
+/// Check that an MCP server launches (stdio) or responds (sse / streamable-http).
+#[command]
+pub fn test_mcp_server(server: McpServerConfig) -> Result<String, String> {
+    let (server_name, tools) = connect_and_list(&server, Duration::from_secs(MCP_TEST_TIMEOUT_SECS))?;
+    Ok(format!(
+        "Connected{}, {} tools available",
+        if server_name.is_empty() { String::new() } else { format!(" to {}", server_name) },
+        tools.len()
+    ))
+}
+
+/// Open a session over the server's transport and list its tools.
+/// Returns the server's self-reported name alongside the tools.
+fn connect_and_list(server: &McpServerConfig, timeout: Duration) -> Result<(String, Vec<McpToolInfo>), String> {
+    let server = &with_resolved_secrets(server)?;
+    match server.server_type.as_str() {
+        "stdio" => {
+            if server.command.trim().is_empty() {
+                return Err("No command configured for stdio server".to_string());
+            }
+            let mut session = mcp_client::StdioSession::connect(stdio_command(server), timeout)?;
+            let tools = session.list_tools()?;
+            Ok((session.server_name.clone(), tools))
+        }
+        "sse" | "streamable-http" => {
+            if server.url.trim().is_empty() {
+                return Err(format!("No URL configured for {} server", server.server_type));
+            }
+            let mut session =
+                mcp_client::HttpSession::connect(&server.url, &server.server_type, http_headers(server), timeout)?;
+            let tools = session.list_tools()?;
+            Ok((session.server_name.clone(), tools))
+        }
+        other => Err(format!("Unknown MCP server type: {}", other)),
+    }
+}
+
+/// A copy of the server config with `keyring:` env values replaced by the
+/// secrets they point to, so secrets only live in memory at spawn time.
+fn with_resolved_secrets(server: &McpServerConfig) -> Result<McpServerConfig, String> {
+    let mut resolved = server.clone();
+    for (key, value) in resolved.env.iter_mut() {
+        if let Some(name) = value.strip_prefix(KEYRING_PREFIX) {
+            *value = read_keyring_secret(name.trim())
+                .map_err(|e| format!("Failed to read keyring entry '{}' for {}: {}", name.trim(), key, e))?;
+        }
+    }
+    Ok(resolved)
+}
+
+/// Look up a generic password in the OS keyring via the platform's CLI.
+fn read_keyring_secret(name: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err("empty keyring entry name".to_string());
+    }
+    let output = keyring_lookup_command(name)
+        .output()
+        .map_err(|e| format!("keyring tool unavailable: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(if stderr.trim().is_empty() { "entry not found".to_string() } else { stderr.trim().to_string() });
+    }
+    let secret = String::from_utf8_lossy(&output.stdout).trim_end_matches(['\r', '\n']).to_string();
+    if secret.is_empty() {
+        return Err("entry is empty".to_string());
+    }
+    Ok(secret)
+}
+
+#[cfg(target_os = "macos")]
+fn keyring_lookup_command(name: &str) -> std::process::Command {
+    let mut cmd = silent_command("security");
+    cmd.args(["find-generic-password", "-s", KEYRING_SERVICE, "-a", name, "-w"]);
+    cmd
+}
+
+#[cfg(target_os = "windows")]
+fn keyring_lookup_command(name: &str) -> std::process::Command {
+    let script = format!(
+        "[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime]; \
+         $c = (New-Object Windows.Security.Credentials.PasswordVault).Retrieve('{}', '{}'); \
+         $c.RetrievePassword(); [Console]::Out.Write($c.Password)",
+        KEYRING_SERVICE,
+        name.replace('\'', "''")
+    );
+    let mut cmd = silent_command("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+    cmd
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn keyring_lookup_command(name: &str) -> std::process::Command {
+    let mut cmd = silent_command("secret-tool");
+    cmd.args(["lookup", "service", KEYRING_SERVICE, "account", name]);
+    cmd
+}
+
+/// Auth headers for an HTTP server, taken from its `env` map: an
+/// `Authorization` entry is sent as-is, `HEADER_<Name>` entries become
+/// `<Name>` headers, and otherwise a `*_TOKEN` / `*_API_KEY` entry is sent
+/// as a bearer token.
+fn http_headers(server: &McpServerConfig) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = server
+        .env
+        .iter()
+        .filter_map(|(key, value)| {
+            if key.eq_ignore_ascii_case("authorization") {
+                Some(("Authorization".to_string(), value.clone()))
+            } else {
+                key.strip_prefix("HEADER_").map(|name| (name.to_string(), value.clone()))
+            }
+        })
+        .collect();
+
+    if !headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("authorization")) {
+        let mut keys: Vec<&String> = server
+            .env
+            .keys()
+            .filter(|k| k.ends_with("_TOKEN") || k.ends_with("_API_KEY"))
+            .collect();
+        keys.sort();
+        if let Some(key) = keys.first() {
+            headers.push(("Authorization".to_string(), format!("Bearer {}", server.env[*key])));
+        }
+    }
+    headers
+}
+
+/// Build the process for a stdio server (resolved via PATH, no console window).
+fn stdio_command(server: &McpServerConfig) -> std::process::Command {
+    let program = find_binary(&server.command).unwrap_or_else(|| server.command.clone());
+    let mut cmd = silent_command(&program);
+    cmd.args(&server.args).envs(&server.env);
+    cmd
+}
+
 /// Get a list of well-known MCP servers that users can quickly add.
 #[command]
 pub fn get_mcp_presets() -> Result<Vec<McpPreset>, String> {
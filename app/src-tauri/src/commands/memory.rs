@@ -9,15 +9,52 @@ pub fn read_consensus(project_dir: String) -> Result<ConsensusState, String> {
     engine::memory::read_consensus(&path)
 }
 
+/// Write the consensus after validating it. Invalid content is rejected unless
+/// `force` is set; returns the validation warnings for the written content.
 #[command]
-pub fn update_consensus(project_dir: String, content: String) -> Result<bool, String> {
+pub fn update_consensus(project_dir: String, content: String, force: Option<bool>) -> Result<Vec<String>, String> {
     let path = PathBuf::from(&project_dir);
-    engine::memory::update_consensus(&path, &content)?;
+    let cycle = engine::memory::read_consensus(&path).map(|c| c.cycle).unwrap_or(0);
+    engine::memory::update_consensus(&path, &content, cycle, force.unwrap_or(false), &required_sections(&path))
+}
+
+#[command]
+pub fn list_consensus_versions(project_dir: String) -> Result<Vec<ConsensusVersion>, String> {
+    let path = PathBuf::from(&project_dir);
+    Ok(engine::memory::list_consensus_versions(&path))
+}
+
+#[command]
+pub fn restore_consensus_version(project_dir: String, version_id: String) -> Result<bool, String> {
+    let path = PathBuf::from(&project_dir);
+    engine::memory::restore_consensus_version(&path, &version_id, &required_sections(&path))?;
     Ok(true)
 }
 
+#[command]
+pub fn diff_consensus(project_dir: String, from_cycle: u32, to_cycle: u32) -> Result<Vec<DiffLine>, String> {
+    let path = PathBuf::from(&project_dir);
+    engine::memory::diff_consensus(&path, from_cycle, to_cycle)
+}
+
 #[command]
 pub fn backup_consensus(project_dir: String) -> Result<String, String> {
     let path = PathBuf::from(&project_dir);
     engine::memory::backup_consensus(&path)
 }
+
+#[command]
+pub fn lint_consensus(project_dir: String) -> Result<Vec<ConsensusLintIssue>, String> {
+    let path = PathBuf::from(&project_dir).join("memories/consensus.md");
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read consensus: {}", e))?;
+    Ok(engine::memory::lint_markdown(&content))
+}
+
+/// The project's `required_consensus_sections`, or the defaults when
+/// company.yaml can't be read.
+fn required_sections(project_dir: &std::path::Path) -> Vec<String> {
+    crate::commands::runtime::load_project_config(project_dir)
+        .map(|config| config.runtime.required_consensus_sections)
+        .unwrap_or_else(|_| default_consensus_sections())
+}
@@ -9,3 +9,4 @@ pub mod provider_presets;
 pub mod mcp;
 pub mod skill_manager;
 pub mod repo_manager;
+pub mod support;
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::command;
 use crate::models::*;
 
@@ -16,31 +16,61 @@ fn mask_key(key: &str) -> String {
 }
 
 /// Scan environment variables and config files for existing API provider configurations.
-/// Returns detected providers along with debug info about paths checked.
+/// When `project_dir` is given, its `.env` file is scanned as well.
 #[command]
-pub fn detect_providers() -> Result<Vec<DetectedProvider>, String> {
+pub fn detect_providers(project_dir: Option<String>) -> Result<Vec<DetectedProvider>, String> {
+    Ok(detect_providers_verbose(project_dir)?.providers)
+}
+
+/// Same scan as `detect_providers`, plus a line for every config path and
+/// endpoint that was examined and what happened to it.
+#[command]
+pub fn detect_providers_verbose(project_dir: Option<String>) -> Result<DetectionReport, String> {
     let mut providers = Vec::new();
+    let mut checked = Vec::new();
 
     // 1. Environment variables (most reliable on Windows GUI apps if set system-wide)
     detect_env_providers(&mut providers);
 
+    // 1b. Project-local .env file
+    if let Some(dir) = project_dir.as_deref().filter(|d| !d.is_empty()) {
+        detect_env_file_providers(&PathBuf::from(dir), &mut providers, &mut checked);
+    }
+
     // 2. Claude Code config
-    detect_claude_config(&mut providers);
+    detect_claude_config(&mut providers, &mut checked);
 
     // 3. Codex CLI config
-    detect_codex_config(&mut providers);
+    detect_codex_config(&mut providers, &mut checked);
 
     // 4. OpenCode config
-    detect_opencode_config(&mut providers);
+    detect_opencode_config(&mut providers, &mut checked);
 
     // 5. Cursor config
-    detect_cursor_config(&mut providers);
+    detect_cursor_config(&mut providers, &mut checked);
+
+    // 6. Local Ollama server
+    detect_ollama(&mut providers, &mut checked);
 
-    // Deduplicate by api_key (keep the first occurrence)
-    let mut seen_keys = std::collections::HashSet::new();
-    providers.retain(|p| seen_keys.insert(p.api_key.clone()));
+    dedup_detected(&mut providers);
 
-    Ok(providers)
+    Ok(DetectionReport { providers, checked })
+}
+
+/// Collapse entries that share provider type, base URL and key. Entries that
+/// differ only in name/source are merged, keeping the ENV-sourced one.
+fn dedup_detected(providers: &mut Vec<DetectedProvider>) {
+    // Stable partition: ENV / ENV_FILE entries win ties, other order is kept
+    providers.sort_by_key(|p| !p.source.starts_with("ENV"));
+
+    let mut seen = std::collections::HashSet::new();
+    providers.retain(|p| {
+        seen.insert((
+            p.provider_type.clone(),
+            p.api_base_url.trim_end_matches('/').to_string(),
+            p.api_key.clone(),
+        ))
+    });
 }
 
 /// Export selected providers as JSON (with masked keys for display).
@@ -94,19 +124,20 @@ pub fn import_providers(json: String) -> Result<AppSettings, String> {
 
 // ===== Detection helpers =====
 
+/// (env var, provider_type, display name, base URL, suggested model)
+const ENV_KEY_PROVIDERS: &[(&str, &str, &str, &str, &str)] = &[
+    ("ANTHROPIC_API_KEY", "anthropic", "Anthropic (Claude)", "https://api.anthropic.com", "claude-sonnet-4-20250514"),
+    ("CLAUDE_API_KEY", "anthropic", "Anthropic (Claude)", "https://api.anthropic.com", "claude-sonnet-4-20250514"),
+    ("OPENAI_API_KEY", "openai", "OpenAI", "https://api.openai.com/v1", "gpt-4o"),
+    ("OPENROUTER_API_KEY", "openrouter", "OpenRouter", "https://openrouter.ai/api/v1", "anthropic/claude-sonnet-4-20250514"),
+    ("GEMINI_API_KEY", "gemini", "Google Gemini", "https://generativelanguage.googleapis.com/v1beta", "gemini-2.5-pro"),
+    ("GOOGLE_API_KEY", "gemini", "Google Gemini", "https://generativelanguage.googleapis.com/v1beta", "gemini-2.5-pro"),
+    ("DEEPSEEK_API_KEY", "deepseek", "DeepSeek", "https://api.deepseek.com", "deepseek-chat"),
+    ("GROQ_API_KEY", "groq", "Groq", "https://api.groq.com/openai/v1", "llama-3.3-70b-versatile"),
+];
+
 fn detect_env_providers(providers: &mut Vec<DetectedProvider>) {
-    let env_configs: &[(&str, &str, &str, &str, &str)] = &[
-        ("ANTHROPIC_API_KEY", "anthropic", "Anthropic (Claude)", "https://api.anthropic.com", "claude-sonnet-4-20250514"),
-        ("CLAUDE_API_KEY", "anthropic", "Anthropic (Claude)", "https://api.anthropic.com", "claude-sonnet-4-20250514"),
-        ("OPENAI_API_KEY", "openai", "OpenAI", "https://api.openai.com/v1", "gpt-4o"),
-        ("OPENROUTER_API_KEY", "openrouter", "OpenRouter", "https://openrouter.ai/api/v1", "anthropic/claude-sonnet-4-20250514"),
-        ("GEMINI_API_KEY", "gemini", "Google Gemini", "https://generativelanguage.googleapis.com/v1beta", "gemini-2.5-pro"),
-        ("GOOGLE_API_KEY", "gemini", "Google Gemini", "https://generativelanguage.googleapis.com/v1beta", "gemini-2.5-pro"),
-        ("DEEPSEEK_API_KEY", "deepseek", "DeepSeek", "https://api.deepseek.com", "deepseek-chat"),
-        ("GROQ_API_KEY", "groq", "Groq", "https://api.groq.com/openai/v1", "llama-3.3-70b-versatile"),
-    ];
-
-    for (env_var, provider_type, name, base_url, model) in env_configs {
+    for (env_var, provider_type, name, base_url, model) in ENV_KEY_PROVIDERS {
         if let Ok(key) = std::env::var(env_var) {
             let trimmed = key.trim().to_string();
             if !trimmed.is_empty() && trimmed.len() > 10 {
@@ -124,20 +155,95 @@ fn detect_env_providers(providers: &mut Vec<DetectedProvider>) {
     }
 }
 
-fn detect_claude_config(providers: &mut Vec<DetectedProvider>) {
+/// Parse `KEY=value` lines from a project's `.env` file (comments, `export`
+/// prefixes and surrounding quotes are handled) for known provider keys.
+fn detect_env_file_providers(project_dir: &Path, providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
+    let Some(content) = read_candidate(&project_dir.join(".env"), checked) else {
+        return;
+    };
+
+    let mut vars = std::collections::HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"').and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            vars.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    for (env_var, provider_type, name, base_url, model) in ENV_KEY_PROVIDERS {
+        if let Some(key) = vars.get(*env_var) {
+            if key.len() > 10 {
+                providers.push(DetectedProvider {
+                    source: "ENV_FILE:.env".to_string(),
+                    provider_type: provider_type.to_string(),
+                    api_key_preview: mask_key(key),
+                    api_key: key.clone(),
+                    api_base_url: base_url.to_string(),
+                    suggested_name: format!("{} (.env)", name),
+                    suggested_model: model.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Probe a local Ollama server and suggest its first installed model.
+fn detect_ollama(providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
+    // Loopback, so this never goes through the proxy
+    let url = "http://localhost:11434/api/tags";
+    let agent = match crate::engine::http::agent_builder(url) {
+        Ok(builder) => builder.timeout(std::time::Duration::from_secs(2)).build(),
+        Err(_) => return,
+    };
+    let json: serde_json::Value = match agent.get(url).call() {
+        Ok(resp) => match resp.into_json() {
+            Ok(v) => v,
+            Err(e) => {
+                checked.push(format!("{}: invalid response ({})", url, e));
+                return;
+            }
+        },
+        Err(e) => {
+            checked.push(format!("{}: not reachable ({})", url, e));
+            return;
+        }
+    };
+    checked.push(format!("{}: reachable", url));
+
+    let model = json
+        .get("models")
+        .and_then(|m| m.as_array())
+        .and_then(|models| models.first())
+        .and_then(|m| m.get("name").or_else(|| m.get("model")))
+        .and_then(|n| n.as_str())
+        .unwrap_or_default();
+
+    providers.push(DetectedProvider {
+        source: "OLLAMA:localhost:11434".to_string(),
+        provider_type: "ollama".to_string(),
+        api_key: String::new(),
+        api_key_preview: String::new(),
+        api_base_url: "http://localhost:11434/v1".to_string(),
+        suggested_name: "Ollama (local)".to_string(),
+        suggested_model: model.to_string(),
+    });
+}
+
+fn detect_claude_config(providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
     let paths = get_claude_config_paths();
 
     for path in paths {
-        if !path.exists() {
+        let Some(json) = read_json_candidate(&path, checked) else {
             continue;
-        }
-        let content = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let json: serde_json::Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => continue,
         };
 
         let source = format!("CC:{}", path.file_name().unwrap_or_default().to_string_lossy());
@@ -210,20 +316,12 @@ fn detect_claude_config(providers: &mut Vec<DetectedProvider>) {
     }
 }
 
-fn detect_codex_config(providers: &mut Vec<DetectedProvider>) {
+fn detect_codex_config(providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
     let paths = get_codex_config_paths();
 
     for path in paths {
-        if !path.exists() {
+        let Some(json) = read_json_candidate(&path, checked) else {
             continue;
-        }
-        let content = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let json: serde_json::Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => continue,
         };
 
         let source = "CDX".to_string();
@@ -247,16 +345,12 @@ fn detect_codex_config(providers: &mut Vec<DetectedProvider>) {
     }
 }
 
-fn detect_opencode_config(providers: &mut Vec<DetectedProvider>) {
+fn detect_opencode_config(providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
     let paths = get_opencode_config_paths();
 
     for path in paths {
-        if !path.exists() {
+        let Some(content) = read_candidate(&path, checked) else {
             continue;
-        }
-        let content = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue,
         };
 
         // Parse TOML with regex-like approach for key=value pairs
@@ -300,7 +394,7 @@ fn detect_opencode_config(providers: &mut Vec<DetectedProvider>) {
     }
 }
 
-fn detect_cursor_config(providers: &mut Vec<DetectedProvider>) {
+fn detect_cursor_config(providers: &mut Vec<DetectedProvider>, checked: &mut Vec<String>) {
     // Cursor stores config in %APPDATA%\Cursor\User\settings.json on Windows
     let mut paths = Vec::new();
 
@@ -312,16 +406,8 @@ fn detect_cursor_config(providers: &mut Vec<DetectedProvider>) {
     }
 
     for path in paths {
-        if !path.exists() {
+        let Some(json) = read_json_candidate(&path, checked) else {
             continue;
-        }
-        let content = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => continue,
-        };
-        let json: serde_json::Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => continue,
         };
 
         // Cursor might store API keys for custom providers
@@ -358,6 +444,36 @@ fn detect_cursor_config(providers: &mut Vec<DetectedProvider>) {
     }
 }
 
+/// Read a candidate config file, recording whether it existed and was readable.
+fn read_candidate(path: &Path, checked: &mut Vec<String>) -> Option<String> {
+    if !path.exists() {
+        checked.push(format!("{}: not found", path.display()));
+        return None;
+    }
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            checked.push(format!("{}: read", path.display()));
+            Some(content)
+        }
+        Err(e) => {
+            checked.push(format!("{}: unreadable ({})", path.display(), e));
+            None
+        }
+    }
+}
+
+/// Like `read_candidate`, but also parses JSON and records parse failures.
+fn read_json_candidate(path: &Path, checked: &mut Vec<String>) -> Option<serde_json::Value> {
+    let content = read_candidate(path, checked)?;
+    match serde_json::from_str(&content) {
+        Ok(json) => Some(json),
+        Err(e) => {
+            checked.push(format!("{}: invalid JSON ({})", path.display(), e));
+            None
+        }
+    }
+}
+
 /// Parse a simple TOML key=value line, stripping quotes from the value.
 fn parse_toml_kv(line: &str) -> Option<(String, String)> {
     let eq_idx = line.find('=')?;
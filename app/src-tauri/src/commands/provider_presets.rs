@@ -136,3 +136,19 @@ fn built_in_presets() -> Vec<ProviderPreset> {
 pub fn get_provider_presets() -> Vec<ProviderPreset> {
     built_in_presets()
 }
+
+/// The first preset model listed for `tier` on a provider type, if any.
+pub fn model_for_tier(provider_type: &str, tier: &str) -> Option<String> {
+    let provider_type = match provider_type {
+        "claude" => "anthropic",
+        "gemini" => "google",
+        other => other,
+    };
+    built_in_presets()
+        .into_iter()
+        .find(|p| p.provider_type == provider_type)?
+        .models
+        .into_iter()
+        .find(|m| m.tier == tier)
+        .map(|m| m.id)
+}
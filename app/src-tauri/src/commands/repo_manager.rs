@@ -11,19 +11,81 @@ fn github_contents_url(owner: &str, repo: &str, path: &str, branch: &str) -> Str
     )
 }
 
+/// GET request through the configured proxy with the GitHub token (if any) attached.
+fn github_request(url: &str) -> Result<ureq::Request, String> {
+    let req = crate::engine::http::agent_builder(url)?
+        .build()
+        .get(url)
+        .set("User-Agent", "omnihive");
+    let token = crate::commands::settings::load_settings()
+        .map(|s| s.github_token)
+        .unwrap_or_default();
+    if token.is_empty() {
+        Ok(req)
+    } else {
+        Ok(req.set("Authorization", &format!("Bearer {}", token)))
+    }
+}
+
 fn github_api_get(url: &str) -> Result<serde_json::Value, String> {
-    let resp = ureq::get(url)
+    let resp = github_request(url)?
         .set("Accept", "application/vnd.github.v3+json")
-        .set("User-Agent", "omnihive")
         .call()
-        .map_err(|e| format!("GitHub API error: {}", e))?;
+        .map_err(|e| match e {
+            ureq::Error::Status(403 | 429, ref resp)
+                if resp.header("x-ratelimit-remaining") == Some("0") =>
+            {
+                "GitHub API rate limit exceeded. Add a GitHub token in Settings to raise the limit.".to_string()
+            }
+            e => format!("GitHub API error: {}", e),
+        })?;
     resp.into_json::<serde_json::Value>()
         .map_err(|e| format!("JSON parse error: {}", e))
 }
 
+/// Fetch a contents listing through the on-disk cache. Entries younger than
+/// `github_cache_ttl_secs` are reused unless `refresh` is set.
+fn github_contents_get(owner: &str, repo: &str, path: &str, branch: &str, refresh: bool) -> Result<serde_json::Value, String> {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (owner, repo, branch, path).hash(&mut hasher);
+    let cache_file = github_cache_dir().join(format!("{:016x}.json", hasher.finish()));
+
+    let ttl = crate::commands::settings::load_settings()
+        .map(|s| s.github_cache_ttl_secs)
+        .unwrap_or(600);
+    let now = chrono::Utc::now().timestamp();
+
+    if !refresh {
+        let cached = fs::read_to_string(&cache_file)
+            .ok()
+            .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok());
+        if let Some(entry) = cached {
+            let fetched_at = entry["fetched_at"].as_i64().unwrap_or(0);
+            if now - fetched_at < ttl as i64 {
+                return Ok(entry["response"].clone());
+            }
+        }
+    }
+
+    let response = github_api_get(&github_contents_url(owner, repo, path, branch))?;
+    let entry = serde_json::json!({"fetched_at": now, "response": response});
+    if fs::create_dir_all(github_cache_dir()).is_ok() {
+        let _ = fs::write(&cache_file, entry.to_string());
+    }
+    Ok(response)
+}
+
+fn github_cache_dir() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("omnihive")
+        .join("github-cache")
+}
+
 fn github_raw_get(url: &str) -> Result<String, String> {
-    let resp = ureq::get(url)
-        .set("User-Agent", "omnihive")
+    let resp = github_request(url)?
         .call()
         .map_err(|e| format!("Download error: {}", e))?;
     resp.into_string()
@@ -62,7 +124,7 @@ pub fn remove_skill_repo(repo_id: String) -> Result<AppSettings, String> {
 // ===== Repo Browsing =====
 
 #[command]
-pub fn browse_repo(repo_id: String, subpath: String) -> Result<Vec<RepoItem>, String> {
+pub fn browse_repo(repo_id: String, subpath: String, refresh: bool) -> Result<Vec<RepoItem>, String> {
     let settings = crate::commands::settings::load_settings()?;
     let repo = settings.skill_repos.iter()
         .find(|r| r.id == repo_id)
@@ -76,8 +138,7 @@ pub fn browse_repo(repo_id: String, subpath: String) -> Result<Vec<RepoItem>, St
         format!("{}/{}", repo.path, subpath)
     };
 
-    let url = github_contents_url(&repo.owner, &repo.repo, &full_path, &repo.branch);
-    let json = github_api_get(&url)?;
+    let json = github_contents_get(&repo.owner, &repo.repo, &full_path, &repo.branch, refresh)?;
 
     let items = json.as_array()
         .ok_or_else(|| "Expected array from GitHub API".to_string())?;
@@ -120,8 +181,7 @@ pub fn browse_repo_skills(repo_id: String) -> Result<Vec<RepoItem>, String> {
         .find(|r| r.id == repo_id)
         .ok_or_else(|| format!("Repository '{}' not found", repo_id))?;
 
-    let url = github_contents_url(&repo.owner, &repo.repo, &repo.path, &repo.branch);
-    let json = github_api_get(&url)?;
+    let json = github_contents_get(&repo.owner, &repo.repo, &repo.path, &repo.branch, false)?;
 
     let items = json.as_array()
         .ok_or_else(|| "Expected array from GitHub API".to_string())?;
@@ -138,12 +198,12 @@ pub fn browse_repo_skills(repo_id: String) -> Result<Vec<RepoItem>, String> {
         }
 
         // Try to fetch SKILL.md description
-        let skill_md_url = github_contents_url(
+        let description = match github_contents_get(
             &repo.owner, &repo.repo,
             &format!("{}/SKILL.md", path),
             &repo.branch,
-        );
-        let description = match github_api_get(&skill_md_url) {
+            false,
+        ) {
             Ok(skill_json) => {
                 if let Some(download) = skill_json["download_url"].as_str() {
                     match github_raw_get(download) {
@@ -186,18 +246,13 @@ pub fn install_repo_skill(repo_id: String, skill_path: String) -> Result<SkillIn
     let items = json.as_array()
         .ok_or_else(|| "Expected array from GitHub API".to_string())?;
 
-    // Determine skill name from path
-    let skill_name = skill_path.split('/')
-        .last()
-        .unwrap_or("unknown-skill")
+    // Determine skill name from path; the repo's directory name is kept as-is
+    let skill_name = validate_repo_skill_name(skill_path.split('/').last().unwrap_or_default())
+        .map_err(|e| format!("Cannot install skill '{}': {}", skill_path, e))?
         .to_string();
 
     // Create the local skill directory
-    let install_dir = dirs::data_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("omnihive")
-        .join("custom-skills")
-        .join(&skill_name);
+    let install_dir = custom_skill_install_dir(&skill_name);
 
     fs::create_dir_all(&install_dir)
         .map_err(|e| format!("Failed to create skill directory: {}", e))?;
@@ -217,6 +272,9 @@ pub fn install_repo_skill(repo_id: String, skill_path: String) -> Result<SkillIn
         if item["type"].as_str() != Some("file") {
             continue;
         }
+        if file_name.is_empty() || file_name.contains(['/', '\\']) || file_name.contains("..") {
+            continue;
+        }
 
         let content = github_raw_get(download_url)?;
 
@@ -245,8 +303,56 @@ pub fn install_repo_skill(repo_id: String, skill_path: String) -> Result<SkillIn
     })
 }
 
+/// Install every skill directory in a repo, skipping ones already installed
+/// locally. A failed skill is reported in `failed` rather than aborting the
+/// batch; this is why the command returns an `InstallAllResult` instead of a
+/// bare `Vec<SkillInfo>` (a GUI app has no visible log to report them in).
+#[command]
+pub fn install_all_repo_skills(repo_id: String) -> Result<InstallAllResult, String> {
+    let skills = browse_repo_skills(repo_id.clone())?;
+
+    let mut installed = Vec::new();
+    let mut failed = Vec::new();
+    for skill in skills {
+        if custom_skill_install_dir(&skill.name).exists() {
+            continue;
+        }
+        match install_repo_skill(repo_id.clone(), skill.path.clone()) {
+            Ok(info) => installed.push(info),
+            Err(error) => failed.push(SkillInstallFailure { path: skill.path, error }),
+        }
+    }
+
+    Ok(InstallAllResult { installed, failed })
+}
+
 // ===== Helpers =====
 
+/// Check a repo skill directory name before using it as the install
+/// directory. Names are kept as the repo has them, so this is looser than
+/// `sanitize_slug`: ASCII letters, digits, `-`, `_` and `.`, with no leading
+/// or trailing `.` and no `..`.
+fn validate_repo_skill_name(name: &str) -> Result<&str, String> {
+    if name.is_empty() || name.starts_with('.') || name.ends_with('.') || name.contains("..") {
+        return Err(format!("Invalid skill name: '{}'", name));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err(format!(
+            "Invalid skill name: '{}' (only A-Z, a-z, 0-9, '-', '_' and '.' are allowed)",
+            name
+        ));
+    }
+    Ok(name)
+}
+
+fn custom_skill_install_dir(skill_name: &str) -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("omnihive")
+        .join("custom-skills")
+        .join(skill_name)
+}
+
 fn parse_first_paragraph(content: &str) -> String {
     let mut found_header = false;
     for line in content.lines() {
@@ -5,9 +5,9 @@ use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
 use std::thread;
 use std::time::Duration;
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 use crate::models::*;
-use crate::engine::api_client;
+use crate::engine::{api_client, cost};
 
 /// Create a Command that suppresses visible console windows on Windows.
 /// On non-Windows platforms this is a plain `Command::new()`.
@@ -29,11 +29,20 @@ pub(crate) fn silent_command(program: &str) -> Command {
 static RUNNING_LOOPS: std::sync::LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
     std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
 
-// Track skill requests per project: project_dir -> Vec<skill_id>
-static PENDING_SKILL_REQUESTS: std::sync::LazyLock<Mutex<HashMap<String, Vec<String>>>> =
+// Track log watchers: project_dir -> stop_flag
+static FOLLOWED_LOGS: std::sync::LazyLock<Mutex<HashMap<String, Arc<AtomicBool>>>> =
     std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
 
+// Track skill requests per agent: (project_dir, role) -> Vec<skill_id>
+type PendingSkills = HashMap<(String, String), Vec<String>>;
+static PENDING_SKILL_REQUESTS: std::sync::LazyLock<Mutex<PendingSkills>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// App handle used by background loops to emit UI events (set on first start_loop)
+static APP_HANDLE: std::sync::OnceLock<AppHandle> = std::sync::OnceLock::new();
+
 /// API credentials resolved at loop start
+#[derive(Clone)]
 struct ApiCredentials {
     engine_type: String,
     api_key: String,
@@ -43,12 +52,179 @@ struct ApiCredentials {
     extra_headers: HashMap<String, String>,
     force_stream: bool,
     api_format: String,
+    include_thinking: bool,
+    extra_body: HashMap<String, serde_json::Value>,
 }
 
 // ===== Tauri Commands =====
 
+/// Payload of the `token-delta` event emitted while a streaming cycle is in progress.
+#[derive(Clone, serde::Serialize)]
+pub struct TokenDelta {
+    pub project_dir: String,
+    pub cycle: u32,
+    pub agent: String,
+    pub text: String,
+}
+
+#[command]
+pub fn start_loop(app: AppHandle, project_dir: String, engine: String, model: String) -> Result<bool, String> {
+    let _ = APP_HANDLE.set(app);
+    launch_loop(project_dir, engine, model, 0)
+}
+
+/// Restart a loop that was still running when the app exited, continuing from
+/// the last recorded cycle instead of starting over at cycle 1.
+#[command]
+pub fn resume_crashed_loop(app: AppHandle, project_dir: String, engine: String, model: String) -> Result<u32, String> {
+    let _ = APP_HANDLE.set(app);
+    let dir = PathBuf::from(&project_dir);
+    let state_file = dir.join(".loop.state");
+
+    let content = std::fs::read_to_string(&state_file)
+        .map_err(|e| format!("Failed to read loop state: {}", e))?;
+    if !content.contains("status=running") && !content.contains("status=interrupted") {
+        return Err("No interrupted loop to resume for this project".to_string());
+    }
+
+    let (current_cycle, _, _, _) = parse_state_file(&state_file);
+    let last_history_cycle = load_cycle_history(&dir)
+        .last()
+        .map(|c| c.cycle_number)
+        .unwrap_or(0);
+    let resume_from = current_cycle.max(last_history_cycle);
+
+    launch_loop(project_dir, engine, model, resume_from)?;
+    append_log(&dir, &format!("Resumed interrupted loop after cycle {}", resume_from));
+    Ok(resume_from)
+}
+
+/// Execute exactly one cycle for `role` without spawning the background loop.
+/// Consensus, memory and handoff are updated as in a normal cycle.
 #[command]
-pub fn start_loop(project_dir: String, engine: String, model: String) -> Result<bool, String> {
+pub fn run_single_cycle(project_dir: String, engine: String, model: String, role: String) -> Result<CycleResult, String> {
+    let dir = PathBuf::from(&project_dir);
+    let config = load_project_config(&dir)?;
+
+    if !config.org.agents.iter().any(|a| a.role == role) {
+        return Err(format!("Role '{}' not found in company.yaml", role));
+    }
+    {
+        let loops = RUNNING_LOOPS.lock().map_err(|e| e.to_string())?;
+        if loops.get(&project_dir).is_some_and(|flag| !flag.load(Ordering::Relaxed)) {
+            return Err("Stop the running loop before running a single cycle".to_string());
+        }
+    }
+    check_foreign_loop_lock(&dir, &config)?;
+
+    let (credentials, _) = resolve_project_credentials(&dir, &engine, &model)?;
+
+    let _ = std::fs::create_dir_all(dir.join("logs"));
+    let (current_cycle, _, _, _) = parse_state_file(&dir.join(".loop.state"));
+    let last_history_cycle = load_cycle_history(&dir).last().map(|c| c.cycle_number).unwrap_or(0);
+    let cycle = current_cycle.max(last_history_cycle) + 1;
+
+    append_log(&dir, &format!("=== Single cycle {} | Agent: {} ===", cycle, role));
+    let started_at = chrono::Local::now().format("%+").to_string();
+    let prompt_ctx = load_prompt_context();
+    let result = run_api_cycle(&project_dir, &credentials, &config, &prompt_ctx, &role, cycle, &AtomicBool::new(false));
+    let completed_at = chrono::Local::now().format("%+").to_string();
+
+    Ok(match result {
+        Ok((output, input_tokens, output_tokens, files_changed)) => {
+            append_log(&dir, &format!(
+                "Single cycle {} completed | Tokens: {}in/{}out",
+                cycle, input_tokens, output_tokens
+            ));
+            CycleResult {
+                cycle_number: cycle,
+                started_at,
+                completed_at,
+                agent_role: role.clone(),
+                action: format!("{} analysis ({}+{} tokens)", role, input_tokens, output_tokens),
+                outcome: truncate_string(&output, 200),
+                files_changed,
+                error: None,
+                input_tokens,
+                output_tokens,
+                cost_usd: cost::cost_usd(&agent_model(&config, &role, &credentials), input_tokens, output_tokens),
+            }
+        }
+        Err(err) => {
+            append_log(&dir, &format!("ERROR: Single cycle {} failed: {}", cycle, err));
+            CycleResult {
+                cycle_number: cycle,
+                started_at,
+                completed_at,
+                agent_role: role.clone(),
+                action: format!("Attempted {} agent cycle", role),
+                outcome: String::new(),
+                files_changed: vec![],
+                error: Some(err),
+                input_tokens: 0,
+                output_tokens: 0,
+                cost_usd: 0.0,
+            }
+        }
+    })
+}
+
+fn load_runtime_override(dir: &Path) -> Option<ProjectRuntimeOverride> {
+    std::fs::read_to_string(dir.join(".runtime_override.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+}
+
+/// An engine/model argument the caller actually chose, as opposed to "use the default".
+fn is_explicit(value: &str) -> bool {
+    !value.is_empty() && value != "auto"
+}
+
+/// Apply the project's runtime override (if any) where the caller didn't pass
+/// an explicit engine/model.
+fn effective_engine_model(dir: &Path, engine: &str, model: &str) -> (String, String) {
+    let ovr = load_runtime_override(dir).unwrap_or_default();
+    let pick = |arg: &str, stored: Option<String>| {
+        if is_explicit(arg) {
+            arg.to_string()
+        } else {
+            stored.filter(|v| !v.is_empty()).unwrap_or_else(|| arg.to_string())
+        }
+    };
+    (pick(engine, ovr.engine), pick(model, ovr.model))
+}
+
+/// Resolve credentials for a project, honouring a pinned `provider_id` in its
+/// runtime override unless the caller chose an engine explicitly.
+/// Returns the credentials and the effective model.
+fn resolve_project_credentials(dir: &Path, engine: &str, model: &str) -> Result<(ApiCredentials, String), String> {
+    let (effective_engine, effective_model) = effective_engine_model(dir, engine, model);
+
+    let pinned = load_runtime_override(dir)
+        .and_then(|o| o.provider_id)
+        .filter(|id| !id.is_empty() && !is_explicit(engine));
+    if let Some(provider_id) = pinned {
+        let settings = load_app_settings()?;
+        let provider = settings
+            .providers
+            .iter()
+            .find(|p| p.id == provider_id)
+            .ok_or_else(|| format!("Pinned provider '{}' not found in settings", provider_id))?;
+        if !crate::commands::settings::provider_has_credentials(provider) {
+            return Err(format!("Pinned provider '{}' has no API key", provider_id));
+        }
+        append_log(dir, &format!("Provider selection: '{}' (pinned by project override)", provider.name));
+        return Ok((credentials_from_provider(provider, &effective_model), effective_model));
+    }
+
+    let (credentials, reason) = resolve_api_credentials_explained(&effective_engine, &effective_model)?;
+    append_log(dir, &format!("Provider selection: {}", reason));
+    Ok((credentials, effective_model))
+}
+
+/// Resolve credentials, register the stop flag and spawn the background loop.
+/// `start_cycle` is the last completed cycle (0 for a fresh run).
+fn launch_loop(project_dir: String, engine: String, model: String, start_cycle: u32) -> Result<bool, String> {
     let dir = PathBuf::from(&project_dir);
 
     // Validate project exists
@@ -66,50 +242,38 @@ pub fn start_loop(project_dir: String, engine: String, model: String) -> Result<
         }
     }
 
-    // Check per-project override first, fall back to global
-    let (effective_engine, effective_model) = {
-        let override_path = dir.join(".runtime_override.json");
-        if override_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&override_path) {
-                if let Ok(ovr) = serde_json::from_str::<ProjectRuntimeOverride>(&content) {
-                    (
-                        ovr.engine.unwrap_or_else(|| engine.clone()),
-                        ovr.model.unwrap_or_else(|| model.clone()),
-                    )
-                } else {
-                    (engine.clone(), model.clone())
-                }
-            } else {
-                (engine.clone(), model.clone())
-            }
-        } else {
-            (engine.clone(), model.clone())
-        }
-    };
-
-    // Resolve API credentials from settings
-    let credentials = resolve_api_credentials(&effective_engine, &effective_model)?;
-
     // Ensure log directory exists
     let _ = std::fs::create_dir_all(dir.join("logs"));
 
+    // Explicit args win, then the per-project override, then the global defaults
+    let (credentials, effective_model) = resolve_project_credentials(&dir, &engine, &model)?;
+    let credential_pool = build_credential_pool(&dir, credentials, &effective_model);
+
     append_log(
         &dir,
         &format!(
             "Starting loop | Engine: {} | Model: {} | Mode: Direct API ({})",
-            engine, model, credentials.api_base_url
+            credential_pool[0].1.engine_type, credential_pool[0].1.model, credential_pool[0].1.api_base_url
         ),
     );
 
+    if credential_pool.len() > 1 {
+        let ids: Vec<&str> = credential_pool.iter().map(|(id, _)| id.as_str()).collect();
+        append_log(&dir, &format!("Load balancing across {} providers: [{}]", ids.len(), ids.join(", ")));
+    }
+
     // Load project config
     let config = load_project_config(&dir)?;
-    let agent_roles: Vec<String> = config.org.agents.iter().map(|a| a.role.clone()).collect();
-    let loop_interval = config.runtime.loop_interval;
-    let cycle_timeout = config.runtime.cycle_timeout;
-    let max_errors = config.runtime.max_consecutive_errors;
+    if !config.org.agents.iter().any(|a| a.enabled) {
+        return Err("No enabled agents in company.yaml".to_string());
+    }
+
+    // Guard against another app instance or script driving the same project
+    acquire_loop_lock(&dir, config.runtime.cycle_timeout as i64 + LOOP_LOCK_GRACE_SECS)?;
 
-    // Update state to running
-    write_state(&dir, "running", 0, 0, 0)?;
+    // Update state to running (fresh file so this session gets its own start timestamp)
+    let _ = std::fs::remove_file(dir.join(".loop.state"));
+    write_state(&dir, "running", start_cycle, start_cycle, 0)?;
 
     // Create stop flag
     let stop_flag = Arc::new(AtomicBool::new(false));
@@ -127,11 +291,9 @@ pub fn start_loop(project_dir: String, engine: String, model: String) -> Result<
         run_loop(
             dir,
             project_dir_clone,
-            credentials,
-            agent_roles,
-            loop_interval,
-            cycle_timeout,
-            max_errors,
+            credential_pool,
+            config,
+            start_cycle,
             stop_clone,
         );
     });
@@ -170,7 +332,7 @@ pub fn resolve_runtime_config(engine: String, model: String) -> Result<ResolvedR
             });
 
         if let Some(provider) = provider {
-            if !provider.api_key.is_empty() {
+            if crate::commands::settings::provider_has_credentials(provider) {
                 let api_base_url = if provider.api_base_url.is_empty() {
                     match engine.as_str() {
                         "claude" => "https://api.anthropic.com".to_string(),
@@ -231,7 +393,7 @@ pub fn resolve_runtime_config(engine: String, model: String) -> Result<ResolvedR
     }
 
     // 3. Auto-detected
-    if let Ok(detected) = crate::commands::provider_detect::detect_providers() {
+    if let Ok(detected) = crate::commands::provider_detect::detect_providers(None) {
         let provider_type = match engine.as_str() {
             "claude" => "anthropic",
             "openai" | "codex" => "openai",
@@ -298,6 +460,56 @@ pub fn stop_loop(project_dir: String) -> Result<bool, String> {
     }
 }
 
+/// Emergency brake: signal every running loop to stop. Returns how many were signalled.
+#[command]
+pub fn stop_all_loops() -> Result<usize, String> {
+    let project_dirs: Vec<String> = {
+        let loops = RUNNING_LOOPS.lock().map_err(|e| e.to_string())?;
+        loops
+            .iter()
+            .filter(|(_, flag)| !flag.swap(true, Ordering::Relaxed))
+            .map(|(dir, _)| dir.clone())
+            .collect()
+    };
+
+    for project_dir in &project_dirs {
+        append_log(Path::new(project_dir), "Stop signal sent by user (stop all loops)");
+    }
+    Ok(project_dirs.len())
+}
+
+/// Loops that are running right now. Loops that were signalled to stop but
+/// haven't exited yet are left out.
+#[command]
+pub fn list_running_loops() -> Result<Vec<RunningLoopInfo>, String> {
+    let project_dirs: Vec<String> = {
+        let loops = RUNNING_LOOPS.lock().map_err(|e| e.to_string())?;
+        loops
+            .iter()
+            .filter(|(_, flag)| !flag.load(Ordering::Relaxed))
+            .map(|(dir, _)| dir.clone())
+            .collect()
+    };
+
+    let now = chrono::Local::now().timestamp();
+    let mut running: Vec<RunningLoopInfo> = project_dirs
+        .into_iter()
+        .map(|project_dir| {
+            let state_file = PathBuf::from(&project_dir).join(".loop.state");
+            let (current_cycle, _, _, last_cycle_at) = parse_state_file(&state_file);
+            let (started, _) = parse_session_times(&std::fs::read_to_string(&state_file).unwrap_or_default());
+            RunningLoopInfo {
+                project_dir,
+                current_cycle,
+                last_cycle_at,
+                uptime_seconds: started.map(|s| (now - s).max(0) as u64).unwrap_or(0),
+            }
+        })
+        .collect();
+    running.sort_by(|a, b| a.project_dir.cmp(&b.project_dir));
+    Ok(running)
+}
+
 #[command]
 pub fn get_status(project_dir: String) -> Result<RuntimeStatus, String> {
     let dir = PathBuf::from(&project_dir);
@@ -316,13 +528,14 @@ pub fn get_status(project_dir: String) -> Result<RuntimeStatus, String> {
     let (current_cycle, total_cycles, consecutive_errors, last_cycle_at) =
         parse_state_file(&state_file);
 
-    // Clean up stale "running" state when loop is not actually tracked
+    // Mark stale "running" state as interrupted when loop is not actually tracked
+    // (the app exited mid-run); resume_crashed_loop can pick it up from here
     if !is_running {
         if let Ok(content) = std::fs::read_to_string(&state_file) {
             if content.contains("status=running") {
                 write_state(
                     &dir,
-                    "stopped",
+                    "interrupted",
                     current_cycle,
                     total_cycles,
                     consecutive_errors,
@@ -340,6 +553,14 @@ pub fn get_status(project_dir: String) -> Result<RuntimeStatus, String> {
         }
     }
 
+    // Uptime of the current session, or total runtime of the last one
+    let (started, stopped) = parse_session_times(&std::fs::read_to_string(&state_file).unwrap_or_default());
+    let uptime_seconds = match (started, stopped) {
+        (Some(start), _) if is_running => (chrono::Local::now().timestamp() - start).max(0) as u64,
+        (Some(start), Some(stop)) => (stop - start).max(0) as u64,
+        _ => 0,
+    };
+
     Ok(RuntimeStatus {
         is_running,
         pid: None,
@@ -347,7 +568,7 @@ pub fn get_status(project_dir: String) -> Result<RuntimeStatus, String> {
         total_cycles,
         consecutive_errors,
         last_cycle_at,
-        uptime_seconds: 0,
+        uptime_seconds,
     })
 }
 
@@ -368,6 +589,97 @@ pub fn get_agent_memory(project_dir: String, role: String) -> Result<String, Str
         .map_err(|e| format!("Failed to read agent memory: {}", e))
 }
 
+/// Every reflection in the agent's memory file, oldest first.
+#[command]
+pub fn get_agent_memory_full(project_dir: String, role: String) -> Result<Vec<MemoryEntry>, String> {
+    let content = get_agent_memory(project_dir, role)?;
+    Ok(content
+        .split("\n---\n")
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(parse_memory_entry)
+        .collect())
+}
+
+const MEMORY_COMPACTION_PROMPT: &str = "You maintain the long-term memory of an AI agent in an autonomous company. \
+Condense the reflections below into one concise summary (at most 300 words) that keeps lasting \
+lessons, decisions, open commitments and recurring problems. Drop one-off details. \
+Respond with the summary only.";
+
+/// Replace everything older than the runtime's memory window with a single
+/// model-written summary entry. Returns how many entries were folded in.
+#[command]
+pub fn compact_agent_memory(project_dir: String, role: String) -> Result<usize, String> {
+    let dir = PathBuf::from(&project_dir);
+    {
+        let loops = RUNNING_LOOPS.lock().map_err(|e| e.to_string())?;
+        if loops.get(&project_dir).is_some_and(|flag| !flag.load(Ordering::Relaxed)) {
+            return Err("Stop the running loop before compacting memory".to_string());
+        }
+    }
+
+    let config = load_project_config(&dir)?;
+    let memory_path = dir.join(format!("memories/agents/{}/MEMORY.md", role));
+    let content = std::fs::read_to_string(&memory_path)
+        .map_err(|e| format!("Failed to read agent memory: {}", e))?;
+    let entries: Vec<&str> = content
+        .split("\n---\n")
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .collect();
+
+    let split = entries.len().saturating_sub(config.runtime.memory_window);
+    if split < 2 {
+        return Ok(0);
+    }
+    let (older, recent) = entries.split_at(split);
+
+    let (engine, model) = effective_engine_model(&dir, "", "");
+    let summary = complete_once(&engine, &model, MEMORY_COMPACTION_PROMPT, &older.join("\n\n---\n\n"))?;
+
+    let cycles: Vec<u32> = older.iter().map(|e| parse_memory_entry(e).cycle).filter(|c| *c > 0).collect();
+    let range = match (cycles.iter().min(), cycles.iter().max()) {
+        (Some(first), Some(last)) => format!("cycles {}-{}", first, last),
+        _ => format!("{} entries", older.len()),
+    };
+    let summary_entry = format!(
+        "**Summary of {} | {}**\n\n{}",
+        range,
+        chrono::Local::now().format("%Y-%m-%d %H:%M"),
+        summary.trim()
+    );
+
+    let rewritten: String = std::iter::once(summary_entry.as_str())
+        .chain(recent.iter().copied())
+        .map(|e| format!("\n---\n{}\n", e))
+        .collect();
+    std::fs::write(&memory_path, rewritten)
+        .map_err(|e| format!("Failed to write agent memory: {}", e))?;
+
+    append_log(&dir, &format!("Compacted {} memory entries for agent {}", older.len(), role));
+    Ok(older.len())
+}
+
+/// Split an entry written by `append_agent_memory` into its
+/// `**Cycle N | timestamp**` header and body. Entries without the header are
+/// kept whole with cycle 0.
+fn parse_memory_entry(entry: &str) -> MemoryEntry {
+    let header = entry
+        .split_once('\n')
+        .map(|(first, rest)| (first.trim(), rest))
+        .filter(|(first, _)| first.starts_with("**Cycle ") && first.ends_with("**"));
+    let Some((first, rest)) = header else {
+        return MemoryEntry { cycle: 0, timestamp: String::new(), text: entry.to_string() };
+    };
+    let inner = first.trim_start_matches("**Cycle ").trim_end_matches("**");
+    let (cycle, timestamp) = inner.split_once('|').unwrap_or((inner, ""));
+    MemoryEntry {
+        cycle: cycle.trim().parse().unwrap_or(0),
+        timestamp: timestamp.trim().to_string(),
+        text: rest.trim().to_string(),
+    }
+}
+
 #[command]
 pub fn get_handoff_note(project_dir: String) -> Result<String, String> {
     let dir = PathBuf::from(&project_dir);
@@ -379,6 +691,27 @@ pub fn get_handoff_note(project_dir: String) -> Result<String, String> {
         .map_err(|e| format!("Failed to read handoff note: {}", e))
 }
 
+/// Recent handoff notes from HANDOFF_LOG.md, oldest first.
+#[command]
+pub fn get_handoff_history(project_dir: String, limit: Option<usize>) -> Result<Vec<String>, String> {
+    let dir = PathBuf::from(&project_dir);
+    let log_path = dir.join("memories/HANDOFF_LOG.md");
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&log_path)
+        .map_err(|e| format!("Failed to read handoff log: {}", e))?;
+
+    let entries: Vec<String> = content
+        .split("\n---\n")
+        .map(|e| e.trim())
+        .filter(|e| !e.is_empty())
+        .map(|e| e.to_string())
+        .collect();
+    let start = entries.len().saturating_sub(limit.unwrap_or(20));
+    Ok(entries[start..].to_vec())
+}
+
 #[command]
 pub fn tail_log(project_dir: String, lines: usize) -> Result<Vec<String>, String> {
     let dir = PathBuf::from(&project_dir);
@@ -409,6 +742,145 @@ pub fn tail_log(project_dir: String, lines: usize) -> Result<Vec<String>, String
     Ok(all_lines[start..].to_vec())
 }
 
+/// Payload of the `log-line` event emitted by `follow_log`.
+#[derive(Clone, serde::Serialize)]
+pub struct LogLine {
+    pub project_dir: String,
+    pub line: String,
+}
+
+/// Stream lines appended to `logs/auto-loop.log` as `log-line` events.
+/// Only bytes written after the watcher starts are read. Following an already
+/// followed project is a no-op.
+#[command]
+pub fn follow_log(app: AppHandle, project_dir: String) -> Result<bool, String> {
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    {
+        let mut followers = FOLLOWED_LOGS.lock().map_err(|e| e.to_string())?;
+        if followers.contains_key(&project_dir) {
+            return Ok(true);
+        }
+        followers.insert(project_dir.clone(), stop_flag.clone());
+    }
+
+    let log_file = PathBuf::from(&project_dir).join("logs/auto-loop.log");
+    let mut offset = std::fs::metadata(&log_file).map(|m| m.len()).unwrap_or(0);
+
+    thread::spawn(move || {
+        let mut partial = String::new();
+        while !stop_flag.load(Ordering::SeqCst) {
+            let len = std::fs::metadata(&log_file).map(|m| m.len()).unwrap_or(0);
+            if len < offset {
+                // Log was truncated or replaced; start over from the top
+                offset = 0;
+                partial.clear();
+            }
+            if len > offset {
+                if let Some(chunk) = read_from_offset(&log_file, offset, len) {
+                    offset = len;
+                    partial.push_str(&String::from_utf8_lossy(&chunk));
+                    while let Some(pos) = partial.find('\n') {
+                        let line = partial[..pos].trim_end_matches('\r').to_string();
+                        partial.drain(..=pos);
+                        let _ = app.emit("log-line", LogLine {
+                            project_dir: project_dir.clone(),
+                            line,
+                        });
+                    }
+                }
+            }
+            thread::sleep(Duration::from_millis(500));
+        }
+    });
+
+    Ok(true)
+}
+
+/// Stop the watcher started by `follow_log`. Returns false if none was running.
+#[command]
+pub fn stop_following_log(project_dir: String) -> Result<bool, String> {
+    let mut followers = FOLLOWED_LOGS.lock().map_err(|e| e.to_string())?;
+    match followers.remove(&project_dir) {
+        Some(flag) => {
+            flag.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+fn read_from_offset(path: &Path, offset: u64, len: u64) -> Option<Vec<u8>> {
+    use std::io::{Read, Seek, SeekFrom};
+    let mut file = std::fs::File::open(path).ok()?;
+    file.seek(SeekFrom::Start(offset)).ok()?;
+    let mut buf = Vec::with_capacity((len - offset) as usize);
+    file.take(len - offset).read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+/// Like `tail_log`, but parsed into `LogEntry` records. Lines are attributed to
+/// the agent named by the most recent `Agent: X` cycle header; continuation
+/// lines are folded into the entry above them.
+#[command]
+pub fn tail_log_structured(project_dir: String, lines: usize) -> Result<Vec<LogEntry>, String> {
+    let log_file = PathBuf::from(&project_dir).join("logs/auto-loop.log");
+    if !log_file.exists() {
+        return Ok(vec![]);
+    }
+    let content = std::fs::read_to_string(&log_file)
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+
+    let entries = parse_log_entries(&content);
+    let start = entries.len().saturating_sub(lines);
+    Ok(entries[start..].to_vec())
+}
+
+fn parse_log_entries(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    let mut current_agent = String::new();
+
+    for line in content.lines() {
+        let parsed = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once("] "));
+        let Some((timestamp, message)) = parsed else {
+            if let Some(last) = entries.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line);
+            }
+            continue;
+        };
+
+        if let Some((_, agent)) = message.split_once("Agent: ") {
+            current_agent = agent.trim_end_matches('=').trim().to_string();
+        }
+        let agent = message
+            .strip_prefix("Agent ")
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(|a| a.to_string())
+            .unwrap_or_else(|| current_agent.clone());
+
+        let level = if message.starts_with("FATAL:") {
+            "fatal"
+        } else if message.starts_with("ERROR:") {
+            "error"
+        } else if message.starts_with("WARNING:") || message.starts_with("GUARDRAIL:") {
+            "warning"
+        } else {
+            "info"
+        };
+
+        entries.push(LogEntry {
+            timestamp: timestamp.to_string(),
+            level: level.to_string(),
+            agent,
+            message: message.to_string(),
+        });
+    }
+
+    entries
+}
+
 // ===== Test API Call =====
 
 #[command]
@@ -424,18 +896,411 @@ pub fn test_api_call(engine: String, model: String, message: String) -> Result<S
         timeout_secs: 30,
         anthropic_version: credentials.anthropic_version,
         extra_headers: credentials.extra_headers,
+        provider_type: credentials.engine_type.clone(),
         force_stream: credentials.force_stream,
         api_format: if credentials.engine_type == "openai" {
             "openai".to_string()
         } else {
             credentials.api_format
         },
+        on_text_delta: None,
+        enable_prompt_cache: false,
+        history: Vec::new(),
+        include_thinking: credentials.include_thinking,
+        extra_body: credentials.extra_body,
     };
 
     let response = api_client::call_api(&api_config)?;
     Ok(format!("[{}in/{}out] {}", response.input_tokens, response.output_tokens, response.text))
 }
 
+/// Send a single system/user prompt pair to the provider resolved for
+/// `engine`/`model` and return the response text.
+pub(crate) fn complete_once(engine: &str, model: &str, system_prompt: &str, user_message: &str) -> Result<String, String> {
+    let credentials = resolve_api_credentials(engine, model)?;
+
+    let api_config = api_client::ApiCallConfig {
+        api_key: credentials.api_key,
+        api_base_url: credentials.api_base_url,
+        model: credentials.model,
+        system_prompt: system_prompt.to_string(),
+        user_message: user_message.to_string(),
+        timeout_secs: 120,
+        anthropic_version: credentials.anthropic_version,
+        extra_headers: credentials.extra_headers,
+        provider_type: credentials.engine_type.clone(),
+        force_stream: credentials.force_stream,
+        api_format: credentials.api_format,
+        on_text_delta: None,
+        enable_prompt_cache: false,
+        history: Vec::new(),
+        include_thinking: credentials.include_thinking,
+        extra_body: credentials.extra_body,
+    };
+
+    Ok(api_client::call_api(&api_config)?.text)
+}
+
+// ===== Prompt Preview =====
+
+#[derive(serde::Serialize)]
+pub struct PromptPreview {
+    pub role: String,
+    pub cycle: u32,
+    pub system_prompt: String,
+    pub user_prompt: String,
+    /// Prior conversation turns sent ahead of the user prompt
+    pub history_turns: usize,
+    pub system_tokens: u32,
+    pub user_tokens: u32,
+    pub history_tokens: u32,
+    pub estimated_tokens: u32,
+}
+
+/// Assemble the exact prompts `role` would receive in `cycle` from the
+/// project's current consensus, memory and handoff, without calling the API.
+/// Pending skill requests are included but left queued.
+#[command]
+pub fn preview_cycle_prompt(project_dir: String, role: String, cycle: u32) -> Result<PromptPreview, String> {
+    let dir = PathBuf::from(&project_dir);
+    let config = load_project_config(&dir)?;
+    if !config.org.agents.iter().any(|a| a.role == role) {
+        return Err(format!("Role '{}' not found in company.yaml", role));
+    }
+
+    let agent_content = read_agent_file(&dir, &role)?;
+    let consensus_content = std::fs::read_to_string(dir.join("memories/consensus.md"))
+        .map_err(|e| format!("Failed to read consensus: {}", e))?;
+    let agent_memory = load_agent_memory(&dir, &role, config.runtime.memory_window, config.runtime.memory_entry_chars);
+    let handoff_note = load_handoff(&dir);
+    let conversation = load_conversation(&dir, &role, config.runtime.conversation_turns);
+    let injected_skills = peek_pending_skills(&project_dir, &role);
+
+    let prompt_ctx = load_prompt_context();
+    let system_prompt = build_system_prompt(&config, &agent_content, &role, cycle, &agent_memory, &injected_skills, &prompt_ctx);
+    let user_prompt = build_user_prompt(&consensus_content, &handoff_note);
+
+    let system_tokens = cost::estimate_tokens(&system_prompt);
+    let user_tokens = cost::estimate_tokens(&user_prompt);
+    let history_tokens: u32 = conversation.iter().map(|m| cost::estimate_tokens(&m.content)).sum();
+
+    Ok(PromptPreview {
+        role,
+        cycle,
+        system_prompt,
+        user_prompt,
+        history_turns: conversation.len() / 2,
+        system_tokens,
+        user_tokens,
+        history_tokens,
+        estimated_tokens: system_tokens + user_tokens + history_tokens,
+    })
+}
+
+// ===== Skill Cost Analysis =====
+
+/// Default prompt budget used when recommending skill trims.
+const DEFAULT_TARGET_PROMPT_TOKENS: u32 = 6000;
+
+#[derive(serde::Serialize)]
+pub struct SkillCost {
+    pub skill_id: String,
+    pub tokens: u32,
+    pub cost_per_cycle_usd: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct SkillCostReport {
+    pub role: String,
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub skill_tokens: u32,
+    pub target_tokens: u32,
+    pub skills: Vec<SkillCost>,
+    pub recommended_disable: Vec<String>,
+}
+
+/// Estimate how many prompt tokens (and dollars per cycle) each injected skill
+/// adds for a role, priced at the model the role uses on the project's
+/// provider, and recommend which skills to disable in the library to reach a
+/// target size.
+#[command]
+pub fn analyze_skill_cost(project_dir: String, role: String, target_tokens: Option<u32>) -> Result<SkillCostReport, String> {
+    let dir = PathBuf::from(&project_dir);
+    let config = load_project_config(&dir)?;
+    let agent = config
+        .org
+        .agents
+        .iter()
+        .find(|a| a.role == role)
+        .ok_or_else(|| format!("Role '{}' not found in company.yaml", role))?;
+
+    let (credentials, _) = resolve_project_credentials(&dir, "", "")?;
+    let model = agent_model(&config, &role, &credentials);
+
+    // Estimate the full prompt the role would receive next cycle
+    let (current_cycle, _, _, _) = parse_state_file(&dir.join(".loop.state"));
+    let agent_content = read_agent_file(&dir, &role)?;
+    let consensus_content = std::fs::read_to_string(dir.join("memories/consensus.md")).unwrap_or_default();
+    let agent_memory = load_agent_memory(&dir, &role, config.runtime.memory_window, config.runtime.memory_entry_chars);
+    let handoff_note = load_handoff(&dir);
+    let prompt_ctx = load_prompt_context();
+    let injected_skills = peek_pending_skills(&project_dir, &role);
+    let system_prompt = build_system_prompt(&config, &agent_content, &role, current_cycle + 1, &agent_memory, &injected_skills, &prompt_ctx);
+    let user_prompt = build_user_prompt(&consensus_content, &handoff_note);
+    let prompt_tokens = cost::estimate_tokens(&system_prompt) + cost::estimate_tokens(&user_prompt);
+
+    let mut skills: Vec<SkillCost> = role_skill_sections(&role, &agent.skills)
+        .into_iter()
+        .map(|(skill_id, section)| {
+            let tokens = cost::estimate_tokens(&section);
+            SkillCost {
+                skill_id,
+                tokens,
+                cost_per_cycle_usd: cost::cost_usd(&model, tokens, 0),
+            }
+        })
+        .collect();
+    skills.sort_by_key(|s| std::cmp::Reverse(s.tokens));
+
+    let skill_tokens: u32 = skills.iter().map(|s| s.tokens).sum();
+    let target = target_tokens.unwrap_or(DEFAULT_TARGET_PROMPT_TOKENS);
+
+    // Drop the most expensive skills first until the prompt fits the target
+    let mut recommended_disable = Vec::new();
+    let mut remaining = prompt_tokens;
+    for skill in &skills {
+        if remaining <= target {
+            break;
+        }
+        recommended_disable.push(skill.skill_id.clone());
+        remaining = remaining.saturating_sub(skill.tokens);
+    }
+
+    Ok(SkillCostReport {
+        role,
+        model,
+        prompt_tokens,
+        skill_tokens,
+        target_tokens: target,
+        skills,
+        recommended_disable,
+    })
+}
+
+// ===== Loop Cost Estimate =====
+
+/// Assumed output per cycle: the rewritten consensus plus a reflection.
+const ESTIMATED_OUTPUT_TOKENS_PER_CYCLE: u32 = 2500;
+
+#[derive(serde::Serialize)]
+pub struct AgentCostEstimate {
+    pub role: String,
+    pub model: String,
+    pub cycles: u32,
+    pub input_tokens_per_cycle: u32,
+    pub output_tokens_per_cycle: u32,
+    pub cost_usd: f64,
+}
+
+#[derive(serde::Serialize)]
+pub struct CostEstimate {
+    pub cycles: u32,
+    pub agents: Vec<AgentCostEstimate>,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_cost_usd: f64,
+}
+
+/// Project the cost of running `cycles` cycles from the agents' current
+/// prompts. Cycles rotate through agents in order, as in the loop, and each
+/// agent is priced at the model its tier maps to on the project's provider.
+#[command]
+pub fn estimate_loop_cost(project_dir: String, model: String, cycles: u32) -> Result<CostEstimate, String> {
+    let dir = PathBuf::from(&project_dir);
+    let config = load_project_config(&dir)?;
+    let enabled: Vec<&AgentConfig> = config.org.agents.iter().filter(|a| a.enabled).collect();
+    let agent_count = enabled.len() as u32;
+    if agent_count == 0 {
+        return Err("No enabled agents in company.yaml".to_string());
+    }
+
+    let consensus_content = std::fs::read_to_string(dir.join("memories/consensus.md")).unwrap_or_default();
+    let handoff_note = load_handoff(&dir);
+    let user_tokens = cost::estimate_tokens(&build_user_prompt(&consensus_content, &handoff_note));
+    let prompt_ctx = load_prompt_context();
+
+    // Price each agent with the model the loop would actually call
+    let (credentials, _) = resolve_project_credentials(&dir, "", &model)?;
+
+    let mut agents = Vec::new();
+    for (i, agent) in enabled.iter().enumerate() {
+        let agent_cycles = cycles / agent_count + u32::from((i as u32) < cycles % agent_count);
+        let agent_model = agent_model(&config, &agent.role, &credentials);
+
+        let agent_content = read_agent_file(&dir, &agent.role)?;
+        let agent_memory = load_agent_memory(&dir, &agent.role, config.runtime.memory_window, config.runtime.memory_entry_chars);
+        let injected_skills = peek_pending_skills(&project_dir, &agent.role);
+        let system_prompt = build_system_prompt(&config, &agent_content, &agent.role, 1, &agent_memory, &injected_skills, &prompt_ctx);
+        let input_tokens = cost::estimate_tokens(&system_prompt) + user_tokens;
+
+        agents.push(AgentCostEstimate {
+            cost_usd: cost::cost_usd(&agent_model, input_tokens, ESTIMATED_OUTPUT_TOKENS_PER_CYCLE) * agent_cycles as f64,
+            role: agent.role.clone(),
+            model: agent_model,
+            cycles: agent_cycles,
+            input_tokens_per_cycle: input_tokens,
+            output_tokens_per_cycle: ESTIMATED_OUTPUT_TOKENS_PER_CYCLE,
+        });
+    }
+
+    Ok(CostEstimate {
+        cycles,
+        total_input_tokens: agents.iter().map(|a| a.input_tokens_per_cycle as u64 * a.cycles as u64).sum(),
+        total_output_tokens: agents.iter().map(|a| a.output_tokens_per_cycle as u64 * a.cycles as u64).sum(),
+        total_cost_usd: agents.iter().map(|a| a.cost_usd).sum(),
+        agents,
+    })
+}
+
+// ===== Agent Order =====
+
+/// Rewrite company.yaml with the agents in `ordered_roles` order, which is
+/// the order round-robin runs them. The roles must be exactly the existing set.
+#[command]
+pub fn reorder_agents(project_dir: String, ordered_roles: Vec<String>) -> Result<Vec<String>, String> {
+    let dir = PathBuf::from(&project_dir);
+    let mut config = load_project_config(&dir)?;
+
+    let mut current: Vec<&str> = config.org.agents.iter().map(|a| a.role.as_str()).collect();
+    let mut requested: Vec<&str> = ordered_roles.iter().map(String::as_str).collect();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(format!(
+            "Roles must match the project's agents exactly: expected [{}], got [{}]",
+            current.join(", "),
+            requested.join(", ")
+        ));
+    }
+
+    let mut agents = std::mem::take(&mut config.org.agents);
+    for role in &ordered_roles {
+        let idx = agents.iter().position(|a| &a.role == role).ok_or_else(|| format!("Role '{}' not found", role))?;
+        config.org.agents.push(agents.remove(idx));
+    }
+
+    save_project_config(&dir, &config)?;
+    Ok(ordered_roles)
+}
+
+/// Include or exclude an agent from loop cycles without removing it from
+/// company.yaml. Takes effect the next time the loop starts.
+#[command]
+pub fn set_agent_enabled(project_dir: String, role: String, enabled: bool) -> Result<bool, String> {
+    let dir = PathBuf::from(&project_dir);
+    let mut config = load_project_config(&dir)?;
+    let agent = config.org.agents.iter_mut()
+        .find(|a| a.role == role)
+        .ok_or_else(|| format!("Role '{}' not found in company.yaml", role))?;
+    agent.enabled = enabled;
+    save_project_config(&dir, &config)?;
+    Ok(enabled)
+}
+
+// ===== Skill Wishlist =====
+
+/// Skills agents requested that aren't in the library, most requested first.
+#[command]
+pub fn get_skill_wishlist(project_dir: String) -> Result<Vec<SkillWish>, String> {
+    let mut wishlist = load_skill_wishlist(&PathBuf::from(&project_dir));
+    wishlist.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.skill_id.cmp(&b.skill_id)));
+    Ok(wishlist)
+}
+
+// ===== Project Notes =====
+
+/// Append a note to `memories/notes.json`, optionally tied to a cycle.
+#[command]
+pub fn add_project_note(project_dir: String, cycle: Option<u32>, text: String) -> Result<ProjectNote, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Note text cannot be empty".to_string());
+    }
+    let dir = PathBuf::from(&project_dir);
+    std::fs::create_dir_all(dir.join("memories"))
+        .map_err(|e| format!("Failed to create memories dir: {}", e))?;
+
+    let note = ProjectNote {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Local::now().format("%+").to_string(),
+        cycle,
+        text: text.to_string(),
+    };
+    let mut notes = load_project_notes(&dir);
+    notes.push(note.clone());
+    let json = serde_json::to_string_pretty(&notes)
+        .map_err(|e| format!("Failed to serialize notes: {}", e))?;
+    std::fs::write(dir.join("memories/notes.json"), json)
+        .map_err(|e| format!("Failed to write notes: {}", e))?;
+    Ok(note)
+}
+
+/// All notes for a project, oldest first.
+#[command]
+pub fn list_project_notes(project_dir: String) -> Result<Vec<ProjectNote>, String> {
+    Ok(load_project_notes(&PathBuf::from(&project_dir)))
+}
+
+fn load_project_notes(dir: &Path) -> Vec<ProjectNote> {
+    std::fs::read_to_string(dir.join("memories/notes.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// ===== Project Files =====
+
+/// Every file in the project (minus VCS and build directories) with its size
+/// and modification time, sorted by category then path.
+#[command]
+pub fn list_project_files(project_dir: String) -> Result<Vec<ProjectFile>, String> {
+    let dir = PathBuf::from(&project_dir);
+    if !dir.join("company.yaml").exists() {
+        return Err("Not a valid project directory (missing company.yaml)".to_string());
+    }
+
+    let mut files: Vec<ProjectFile> = walk_project_files(&dir, &[".git", "node_modules", "target"])
+        .into_iter()
+        .map(|(path, (modified, size))| ProjectFile {
+            category: project_file_category(&path).to_string(),
+            modified: modified
+                .map(|t| chrono::DateTime::<chrono::Local>::from(t).format("%+").to_string())
+                .unwrap_or_default(),
+            path,
+            size,
+        })
+        .collect();
+    files.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.path.cmp(&b.path)));
+    Ok(files)
+}
+
+fn project_file_category(path: &str) -> &'static str {
+    if path.starts_with(".claude/agents/") {
+        "agent"
+    } else if path.starts_with("memories/") {
+        "memory"
+    } else if path.starts_with("docs/") || path == "README.md" || path == "CLAUDE.md" {
+        "doc"
+    } else if path.starts_with("logs/") {
+        "log"
+    } else if path == "company.yaml" || path.starts_with(".claude/") || path.starts_with("scripts/") || (!path.contains('/') && path.starts_with('.')) {
+        "config"
+    } else {
+        "other"
+    }
+}
+
 // ===== Per-Project Runtime Override =====
 
 #[command]
@@ -454,6 +1319,12 @@ pub fn get_project_runtime_override(project_dir: String) -> Result<ProjectRuntim
 #[command]
 pub fn set_project_runtime_override(project_dir: String, config: ProjectRuntimeOverride) -> Result<bool, String> {
     let dir = PathBuf::from(&project_dir);
+    if let Some(id) = config.provider_id.as_deref().filter(|id| !id.is_empty()) {
+        let settings = load_app_settings()?;
+        if !settings.providers.iter().any(|p| p.id == id) {
+            return Err(format!("Provider '{}' not found", id));
+        }
+    }
     let override_path = dir.join(".runtime_override.json");
     let json = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize override: {}", e))?;
@@ -462,12 +1333,124 @@ pub fn set_project_runtime_override(project_dir: String, config: ProjectRuntimeO
     Ok(true)
 }
 
+// ===== Per-Project Load Balancing =====
+
+#[command]
+pub fn get_load_balance(project_dir: String) -> Result<LoadBalanceConfig, String> {
+    let dir = PathBuf::from(&project_dir);
+    Ok(load_load_balance(&dir))
+}
+
+/// Configure the set of same-type providers a project's loop rotates through.
+/// Existing usage counters are preserved so fairness carries across restarts.
+#[command]
+pub fn set_load_balance(project_dir: String, enabled: bool, provider_ids: Vec<String>) -> Result<LoadBalanceConfig, String> {
+    let dir = PathBuf::from(&project_dir);
+
+    if enabled {
+        if provider_ids.len() < 2 {
+            return Err("Load balancing needs at least two providers".to_string());
+        }
+        let settings = load_app_settings()?;
+        let mut provider_type: Option<&str> = None;
+        for id in &provider_ids {
+            let provider = settings
+                .providers
+                .iter()
+                .find(|p| &p.id == id)
+                .ok_or_else(|| format!("Provider '{}' not found", id))?;
+            match provider_type {
+                None => provider_type = Some(provider.provider_type.as_str()),
+                Some(t) if t != provider.provider_type => {
+                    return Err(format!(
+                        "Load-balanced providers must share a type ('{}' is {}, expected {})",
+                        id, provider.provider_type, t
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    let mut config = load_load_balance(&dir);
+    config.enabled = enabled;
+    config.provider_ids = provider_ids;
+    config.usage.retain(|id, _| config.provider_ids.contains(id));
+
+    let json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize load balance config: {}", e))?;
+    std::fs::write(dir.join(".load_balance.json"), json)
+        .map_err(|e| format!("Failed to write load balance config: {}", e))?;
+    Ok(config)
+}
+
+fn load_load_balance(dir: &Path) -> LoadBalanceConfig {
+    std::fs::read_to_string(dir.join(".load_balance.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn save_load_balance(dir: &Path, config: &LoadBalanceConfig) {
+    if let Ok(json) = serde_json::to_string_pretty(config) {
+        let _ = std::fs::write(dir.join(".load_balance.json"), json);
+    }
+}
+
+/// Expand the resolved credentials into a rotation pool when the project has
+/// load balancing enabled. Falls back to the single resolved provider otherwise.
+fn build_credential_pool(dir: &Path, credentials: ApiCredentials, model: &str) -> Vec<(String, ApiCredentials)> {
+    let balance = load_load_balance(dir);
+    if balance.enabled && balance.provider_ids.len() > 1 {
+        if let Ok(settings) = load_app_settings() {
+            let pool: Vec<(String, ApiCredentials)> = balance
+                .provider_ids
+                .iter()
+                .filter_map(|id| settings.providers.iter().find(|p| &p.id == id))
+                .filter(|p| p.enabled && crate::commands::settings::provider_has_credentials(p))
+                .map(|p| (p.id.clone(), credentials_from_provider(p, model)))
+                .collect();
+            if pool.len() > 1 {
+                return pool;
+            }
+        }
+        append_log(dir, "WARNING: Load balancing enabled but fewer than two usable providers; using a single provider");
+    }
+    vec![(String::new(), credentials)]
+}
+
+/// Pick the pool entry with the fewest recorded cycles (ties go to pool order).
+fn select_balanced_provider<'a>(
+    pool: &'a [(String, ApiCredentials)],
+    balance: &LoadBalanceConfig,
+) -> (&'a String, &'a ApiCredentials) {
+    let (id, creds) = pool
+        .iter()
+        .min_by_key(|(id, _)| balance.usage.get(id).map(|u| u.cycles).unwrap_or(0))
+        .unwrap_or(&pool[0]);
+    (id, creds)
+}
+
 // ===== Project Events (Activity Feed) =====
 
-// Track events per project: project_dir -> Vec<ProjectEvent>
+// Track events per project: project_dir -> Vec<ProjectEvent>, mirrored to memories/events.json
 static PROJECT_EVENTS: std::sync::LazyLock<Mutex<HashMap<String, Vec<ProjectEvent>>>> =
     std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
 
+/// Number of events kept per project, in memory and on disk.
+const MAX_PROJECT_EVENTS: usize = 200;
+
+fn events_path(project_dir: &str) -> PathBuf {
+    PathBuf::from(project_dir).join("memories/events.json")
+}
+
+fn load_persisted_events(project_dir: &str) -> Vec<ProjectEvent> {
+    std::fs::read_to_string(events_path(project_dir))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
 fn emit_project_event(project_dir: &str, event_type: &str, agent: &str, summary: &str, details: &str) {
     let event = ProjectEvent {
         id: format!("{}-{}", chrono::Local::now().timestamp_millis(), agent),
@@ -479,26 +1462,50 @@ fn emit_project_event(project_dir: &str, event_type: &str, agent: &str, summary:
     };
 
     if let Ok(mut map) = PROJECT_EVENTS.lock() {
-        let events = map.entry(project_dir.to_string()).or_default();
+        // Pick up the feed from earlier sessions before appending
+        let events = map
+            .entry(project_dir.to_string())
+            .or_insert_with(|| load_persisted_events(project_dir));
         events.push(event);
-        // Keep last 200 events
-        if events.len() > 200 {
-            let drain_count = events.len() - 200;
+        if events.len() > MAX_PROJECT_EVENTS {
+            let drain_count = events.len() - MAX_PROJECT_EVENTS;
             events.drain(..drain_count);
         }
+        if let Ok(json) = serde_json::to_string_pretty(events) {
+            let _ = std::fs::write(events_path(project_dir), json);
+        }
     }
 }
 
+/// Send a loop event to the configured webhook on a background thread so a
+/// slow or unreachable endpoint never holds up the loop. Failures are logged.
+fn notify_webhook(dir: &Path, project_dir: &str, event_type: &str, cycle: u32, summary: &str) {
+    let Some(url) = crate::engine::webhook::webhook_url() else {
+        return;
+    };
+    let dir = dir.to_path_buf();
+    let project = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| project_dir.to_string());
+    let (event_type, summary) = (event_type.to_string(), summary.to_string());
+    thread::spawn(move || {
+        if let Err(e) = crate::engine::webhook::post_event(&url, &project, &event_type, cycle, &summary) {
+            append_log(&dir, &format!("WARNING: Webhook notification failed: {}", e));
+        }
+    });
+}
+
 #[command]
 pub fn get_project_events(project_dir: String, limit: Option<usize>) -> Result<Vec<ProjectEvent>, String> {
     let max = limit.unwrap_or(50);
-    if let Ok(map) = PROJECT_EVENTS.lock() {
-        if let Some(events) = map.get(&project_dir) {
-            let start = if events.len() > max { events.len() - max } else { 0 };
-            return Ok(events[start..].to_vec());
-        }
-    }
-    Ok(Vec::new())
+    let events = PROJECT_EVENTS
+        .lock()
+        .ok()
+        .and_then(|map| map.get(&project_dir).cloned())
+        .unwrap_or_else(|| load_persisted_events(&project_dir));
+    let start = events.len().saturating_sub(max);
+    Ok(events[start..].to_vec())
 }
 
 // ===== Auto Provider Selection =====
@@ -522,7 +1529,7 @@ fn auto_select_provider_internal() -> Result<(ApiCredentials, SelectedProvider),
     // 1. Check configured providers (enabled + healthy first)
     if let Ok(settings) = load_app_settings() {
         let mut candidates: Vec<&AiProvider> = settings.providers.iter()
-            .filter(|p| p.enabled && !p.api_key.is_empty())
+            .filter(|p| p.enabled && crate::commands::settings::provider_has_credentials(p))
             .collect();
 
         // Sort by: healthy first, then by priority order
@@ -563,6 +1570,8 @@ fn auto_select_provider_internal() -> Result<(ApiCredentials, SelectedProvider),
                 extra_headers: provider.extra_headers.clone(),
                 force_stream: provider.force_stream,
                 api_format: api_format.to_string(),
+                include_thinking: provider.include_thinking,
+                extra_body: provider.extra_body.clone(),
             };
             let selected = SelectedProvider {
                 provider_id: provider.id.clone(),
@@ -599,6 +1608,8 @@ fn auto_select_provider_internal() -> Result<(ApiCredentials, SelectedProvider),
                     extra_headers: HashMap::new(),
                     force_stream: false,
                     api_format: api_format.to_string(),
+                    include_thinking: false,
+                    extra_body: HashMap::new(),
                 };
                 let selected = SelectedProvider {
                     provider_id: format!("env-{}", ptype),
@@ -614,7 +1625,7 @@ fn auto_select_provider_internal() -> Result<(ApiCredentials, SelectedProvider),
     }
 
     // 3. Auto-detected providers
-    if let Ok(detected) = crate::commands::provider_detect::detect_providers() {
+    if let Ok(detected) = crate::commands::provider_detect::detect_providers(None) {
         if let Some(dp) = detected.first() {
             let (api_format, _) = derive_api_config(&dp.provider_type);
             let creds = ApiCredentials {
@@ -626,6 +1637,8 @@ fn auto_select_provider_internal() -> Result<(ApiCredentials, SelectedProvider),
                 extra_headers: HashMap::new(),
                 force_stream: false,
                 api_format: api_format.to_string(),
+                include_thinking: false,
+                extra_body: HashMap::new(),
             };
             let selected = SelectedProvider {
                 provider_id: format!("auto-{}", dp.provider_type),
@@ -651,6 +1664,23 @@ pub fn auto_select_provider() -> Result<SelectedProvider, String> {
 // ===== API Credential Resolution =====
 
 fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials, String> {
+    resolve_api_credentials_explained(engine, model).map(|(credentials, _)| credentials)
+}
+
+/// Pick among enabled settings providers with credentials, preferring ones
+/// whose last health check passed. Returns the provider and why it was chosen.
+fn pick_settings_provider<'a>(candidates: &[&'a AiProvider]) -> Option<(&'a AiProvider, String)> {
+    if let Some(provider) = candidates.iter().find(|p| p.is_healthy) {
+        return Some((provider, format!("'{}' (healthy)", provider.name)));
+    }
+    candidates.first().map(|provider| {
+        let why = provider.last_error.as_deref().unwrap_or("not yet checked");
+        (*provider, format!("'{}' (no healthy match; last health check: {})", provider.name, why))
+    })
+}
+
+/// Like `resolve_api_credentials`, also describing where the credentials came from.
+fn resolve_api_credentials_explained(engine: &str, model: &str) -> Result<(ApiCredentials, String), String> {
     use crate::commands::settings::derive_api_config;
 
     // If engine is "auto" or empty, use auto-select
@@ -660,7 +1690,8 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
         if !model.is_empty() && model != "auto" {
             creds.model = model.to_string();
         }
-        return Ok(creds);
+        let reason = format!("auto-selected {} provider", creds.engine_type);
+        return Ok((creds, reason));
     }
 
     // 1. Try app-level settings (stored providers) — prefer engine field match
@@ -671,60 +1702,29 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
             other => other,
         };
 
+        let usable = |p: &&AiProvider| p.enabled && crate::commands::settings::provider_has_credentials(p);
         // First: match by provider_type
-        let provider = settings
+        let by_type: Vec<&AiProvider> = settings
             .providers
-            .iter()
-            .find(|p| p.enabled && (p.provider_type == provider_type || p.provider_type == engine))
-            // Fallback: match by engine field (legacy)
-            .or_else(|| {
-                settings
-                    .providers
-                    .iter()
-                    .find(|p| p.enabled && p.engine == engine)
-            });
-
-        if let Some(provider) = provider {
-            if !provider.api_key.is_empty() {
-                let (derived_format, derived_url) = derive_api_config(&provider.provider_type);
-
-                let api_base_url = if provider.api_base_url.is_empty() {
-                    derived_url.to_string()
-                } else {
-                    provider.api_base_url.clone()
-                };
-
-                // Use provider's default_model if it looks like a full model ID,
-                // otherwise fall back to the tier name from settings
-                let resolved_model = if !provider.default_model.is_empty()
-                    && provider.default_model.contains('-')
-                {
-                    provider.default_model.clone()
-                } else {
-                    model.to_string()
-                };
-
-                let api_format = if !provider.api_format.is_empty() {
-                    provider.api_format.clone()
-                } else {
-                    derived_format.to_string()
-                };
-
-                return Ok(ApiCredentials {
-                    engine_type: provider.provider_type.clone(),
-                    api_key: provider.api_key.clone(),
-                    api_base_url,
-                    model: resolved_model,
-                    anthropic_version: if provider.anthropic_version.is_empty() {
-                        "2023-06-01".to_string()
-                    } else {
-                        provider.anthropic_version.clone()
-                    },
-                    extra_headers: provider.extra_headers.clone(),
-                    force_stream: provider.force_stream,
-                    api_format,
-                });
-            }
+            .iter()
+            .filter(usable)
+            .filter(|p| p.provider_type == provider_type || p.provider_type == engine)
+            .collect();
+        // Fallback: match by engine field (legacy)
+        let by_engine: Vec<&AiProvider> = settings.providers.iter().filter(usable).filter(|p| p.engine == engine).collect();
+
+        // The user's chosen default for this type wins over first-match
+        let preferred = settings
+            .default_provider_ids
+            .get(provider_type)
+            .and_then(|id| by_type.iter().find(|p| &p.id == id))
+            .map(|p| (*p, format!("'{}' (default for {})", p.name, provider_type)));
+
+        if let Some((provider, reason)) = preferred
+            .or_else(|| pick_settings_provider(&by_type))
+            .or_else(|| pick_settings_provider(&by_engine))
+        {
+            return Ok((credentials_from_provider(provider, model), reason));
         }
     }
 
@@ -743,7 +1743,7 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
         if let Ok(key) = std::env::var(env_var) {
             if !key.trim().is_empty() {
                 let (api_format, base_url) = derive_api_config(ptype);
-                return Ok(ApiCredentials {
+                let credentials = ApiCredentials {
                     engine_type: ptype.to_string(),
                     api_key: key.trim().to_string(),
                     api_base_url: base_url.to_string(),
@@ -752,13 +1752,16 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
                     extra_headers: HashMap::new(),
                     force_stream: false,
                     api_format: api_format.to_string(),
-                });
+                    include_thinking: false,
+                    extra_body: HashMap::new(),
+                };
+                return Ok((credentials, format!("{} environment variable", env_var)));
             }
         }
     }
 
     // 3. Try auto-detected providers
-    if let Ok(detected) = crate::commands::provider_detect::detect_providers() {
+    if let Ok(detected) = crate::commands::provider_detect::detect_providers(None) {
         let provider_type = match engine {
             "claude" => "anthropic",
             "openai" | "codex" => "openai",
@@ -766,7 +1769,7 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
         };
         if let Some(dp) = detected.iter().find(|d| d.provider_type == provider_type) {
             let (api_format, _) = derive_api_config(&dp.provider_type);
-            return Ok(ApiCredentials {
+            let credentials = ApiCredentials {
                 engine_type: dp.provider_type.clone(),
                 api_key: dp.api_key.clone(),
                 api_base_url: dp.api_base_url.clone(),
@@ -775,7 +1778,10 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
                 extra_headers: HashMap::new(),
                 force_stream: false,
                 api_format: api_format.to_string(),
-            });
+                include_thinking: false,
+                extra_body: HashMap::new(),
+            };
+            return Ok((credentials, format!("auto-detected {} credentials", dp.provider_type)));
         }
     }
 
@@ -795,6 +1801,52 @@ fn resolve_api_credentials(engine: &str, model: &str) -> Result<ApiCredentials,
     ))
 }
 
+/// Build API credentials from a configured provider entry.
+fn credentials_from_provider(provider: &AiProvider, model: &str) -> ApiCredentials {
+    use crate::commands::settings::derive_api_config;
+
+    let (derived_format, derived_url) = derive_api_config(&provider.provider_type);
+
+    let api_base_url = if provider.api_base_url.is_empty() {
+        derived_url.to_string()
+    } else {
+        provider.api_base_url.clone()
+    };
+
+    // Use provider's default_model if it looks like a full model ID,
+    // otherwise fall back to the tier name from settings
+    let resolved_model = if !provider.default_model.is_empty()
+        && provider.default_model.contains('-')
+    {
+        provider.default_model.clone()
+    } else {
+        model.to_string()
+    };
+
+    let api_format = if !provider.api_format.is_empty() {
+        provider.api_format.clone()
+    } else {
+        derived_format.to_string()
+    };
+
+    ApiCredentials {
+        engine_type: provider.provider_type.clone(),
+        api_key: provider.api_key.clone(),
+        api_base_url,
+        model: resolved_model,
+        anthropic_version: if provider.anthropic_version.is_empty() {
+            "2023-06-01".to_string()
+        } else {
+            provider.anthropic_version.clone()
+        },
+        extra_headers: provider.extra_headers.clone(),
+        force_stream: provider.force_stream,
+        api_format,
+        include_thinking: provider.include_thinking,
+        extra_body: provider.extra_body.clone(),
+    }
+}
+
 fn load_app_settings() -> Result<AppSettings, String> {
     let path = dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -807,7 +1859,10 @@ fn load_app_settings() -> Result<AppSettings, String> {
 
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read settings: {}", e))?;
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))
+    let settings: AppSettings = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    crate::engine::http::set_proxy_url(&settings.proxy_url);
+    Ok(settings)
 }
 
 // ===== Background Loop =====
@@ -815,25 +1870,33 @@ fn load_app_settings() -> Result<AppSettings, String> {
 fn run_loop(
     dir: PathBuf,
     project_dir: String,
-    credentials: ApiCredentials,
-    agent_roles: Vec<String>,
-    loop_interval: u32,
-    cycle_timeout: u32,
-    max_errors: u32,
+    credential_pool: Vec<(String, ApiCredentials)>,
+    config: FactoryConfig,
+    start_cycle: u32,
     stop_flag: Arc<AtomicBool>,
 ) {
-    let mut cycle: u32 = 0;
+    let agent_roles: Vec<String> = config.org.agents.iter().filter(|a| a.enabled).map(|a| a.role.clone()).collect();
+    let loop_interval = config.runtime.loop_interval;
+    let cycle_timeout = config.runtime.cycle_timeout;
+    let max_errors = config.runtime.max_consecutive_errors;
+    let mut cycle: u32 = start_cycle;
     let mut errors: u32 = 0;
     let mut history: Vec<CycleResult> = load_cycle_history(&dir);
+    let mut balance = load_load_balance(&dir);
+    let prompt_ctx = load_prompt_context();
+    let consensus_path = dir.join("memories/consensus.md");
+    let mut last_consensus = std::fs::read_to_string(&consensus_path).unwrap_or_default();
+    let mut unchanged_cycles: usize = 0;
 
     append_log(
         &dir,
         &format!(
-            "Loop started | {} agents: [{}] | interval={}s timeout={}s max_errors={}",
+            "Loop started | {} agents: [{}] | interval={}s timeout={}s request_timeout={}s max_errors={}",
             agent_roles.len(),
             agent_roles.join(", "),
             loop_interval,
             cycle_timeout,
+            config.runtime.request_timeout,
             max_errors,
         ),
     );
@@ -853,17 +1916,60 @@ fn run_loop(
             &dir,
             &format!("=== Cycle {} | Agent: {} ===", cycle, current_agent),
         );
+        emit_project_event(&project_dir, "cycle_start", current_agent, &format!("Cycle {} started", cycle), "");
 
         let started_at = chrono::Local::now().format("%+").to_string();
         write_state(&dir, "running", cycle, cycle, errors).ok();
+        refresh_loop_lock(&dir);
+
+        // Pick the provider for this cycle (least-used first when load balancing)
+        let (provider_id, credentials) = select_balanced_provider(&credential_pool, &balance);
+        if credential_pool.len() > 1 {
+            append_log(&dir, &format!("Load balancer selected provider '{}'", provider_id));
+        }
+
+        // Execute API cycle on a worker so cycle_timeout is a real wall-clock limit
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let result = {
+            let (project_dir, credentials, config, prompt_ctx, agent, cancelled) = (
+                project_dir.clone(),
+                credentials.clone(),
+                config.clone(),
+                prompt_ctx.clone(),
+                current_agent.clone(),
+                cancelled.clone(),
+            );
+            run_with_deadline(cycle_timeout, move || {
+                run_api_cycle(&project_dir, &credentials, &config, &prompt_ctx, &agent, cycle, &cancelled)
+            })
+        }
+        .unwrap_or_else(|e| match e {
+            DeadlineError::Timeout => {
+                // Stop the abandoned worker from writing once the next cycle starts
+                cancelled.store(true, Ordering::SeqCst);
+                append_log(&dir, &format!("Cycle {} timed out after {}s", cycle, cycle_timeout));
+                Err(format!("Cycle {} timed out after {}s", cycle, cycle_timeout))
+            }
+            DeadlineError::Panicked(message) => {
+                append_log(&dir, &format!("Cycle {} worker panicked: {}", cycle, message));
+                Err(format!("Cycle {} worker panicked: {}", cycle, message))
+            }
+        });
 
-        // Execute API cycle
-        let result = run_api_cycle(&dir, &project_dir, &credentials, current_agent, cycle, cycle_timeout);
+        if credential_pool.len() > 1 {
+            let usage = balance.usage.entry(provider_id.clone()).or_default();
+            usage.cycles += 1;
+            if let Ok((_, input_tokens, output_tokens, _)) = &result {
+                usage.input_tokens += *input_tokens as u64;
+                usage.output_tokens += *output_tokens as u64;
+            }
+            save_load_balance(&dir, &balance);
+        }
 
         let completed_at = chrono::Local::now().format("%+").to_string();
 
         match result {
-            Ok((output, input_tokens, output_tokens)) => {
+            Ok((output, input_tokens, output_tokens, files_changed)) => {
                 errors = 0;
                 let preview = truncate_string(&output, 200);
                 append_log(
@@ -892,17 +1998,47 @@ fn run_loop(
                         current_agent, input_tokens, output_tokens
                     ),
                     outcome: preview,
-                    files_changed: vec![],
+                    files_changed,
                     error: None,
+                    input_tokens,
+                    output_tokens,
+                    cost_usd: cost::cost_usd(&agent_model(&config, current_agent, credentials), input_tokens, output_tokens),
                 });
+
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                let spent = daily_spend(&history, &today);
+                let spent_before = spent - history.last().map_or(0.0, |c| c.cost_usd);
+                let alert_at = config.runtime.budget.alert_at_usd;
+                if alert_at > 0.0 && spent_before < alert_at && spent >= alert_at {
+                    let summary = format!("Daily spend ${:.2} reached the ${:.2} alert threshold", spent, alert_at);
+                    append_log(&dir, &format!("WARNING: {}", summary));
+                    emit_project_event(&project_dir, "budget_alert", current_agent, &summary, "");
+                    notify_webhook(&dir, &project_dir, "budget_alert", cycle, &summary);
+                }
+
+                // Converged once a full rotation of agents leaves consensus untouched
+                let consensus = std::fs::read_to_string(&consensus_path).unwrap_or_default();
+                if consensus == last_consensus {
+                    unchanged_cycles += 1;
+                    if unchanged_cycles == agent_roles.len() {
+                        let summary = format!("Consensus unchanged for {} cycles (a full rotation)", unchanged_cycles);
+                        append_log(&dir, &format!("Converged: {}", summary));
+                        emit_project_event(&project_dir, "converged", current_agent, &summary, "");
+                        notify_webhook(&dir, &project_dir, "converged", cycle, &summary);
+                    }
+                } else {
+                    unchanged_cycles = 0;
+                    last_consensus = consensus;
+                }
             }
             Err(err) => {
                 errors += 1;
+                let kind = api_client::ApiErrorKind::classify(&err);
                 append_log(
                     &dir,
                     &format!(
-                        "ERROR: Cycle {} failed: {} (consecutive: {})",
-                        cycle, err, errors
+                        "ERROR: Cycle {} failed [{}]: {} (consecutive: {})",
+                        cycle, kind.label(), err, errors
                     ),
                 );
 
@@ -910,9 +2046,16 @@ fn run_loop(
                     &project_dir,
                     "cycle_error",
                     current_agent,
-                    &format!("Cycle {} failed (error {})", cycle, errors),
+                    &format!("Cycle {} failed (error {}): {}", cycle, errors, kind.hint()),
                     &truncate_string(&err, 200),
                 );
+                notify_webhook(
+                    &dir,
+                    &project_dir,
+                    "cycle_error",
+                    cycle,
+                    &format!("{} agent failed [{}]: {}", current_agent, kind.label(), truncate_string(&err, 200)),
+                );
 
                 history.push(CycleResult {
                     cycle_number: cycle,
@@ -923,8 +2066,23 @@ fn run_loop(
                     outcome: String::new(),
                     files_changed: vec![],
                     error: Some(err),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    cost_usd: 0.0,
                 });
 
+                // With a single provider, retrying can't fix bad credentials, quota
+                // or model names; a rotation pool may still have working entries.
+                if kind.is_fatal() && credential_pool.len() == 1 {
+                    append_log(&dir, &format!("FATAL: {} Stopping loop.", kind.hint()));
+                    emit_project_event(&project_dir, "loop_stopped", current_agent, kind.hint(), "");
+                    notify_webhook(&dir, &project_dir, "loop_stopped", cycle, &format!("Fatal error: {}", kind.hint()));
+                    write_state(&dir, "error", cycle, cycle, errors).ok();
+                    save_cycle_history(&dir, &history);
+                    cleanup_loop(&project_dir);
+                    return;
+                }
+
                 if errors >= max_errors {
                     append_log(
                         &dir,
@@ -933,11 +2091,25 @@ fn run_loop(
                             max_errors
                         ),
                     );
+                    notify_webhook(
+                        &dir,
+                        &project_dir,
+                        "loop_stopped",
+                        cycle,
+                        &format!("Max consecutive errors ({}) reached", max_errors),
+                    );
                     write_state(&dir, "error", cycle, cycle, errors).ok();
                     save_cycle_history(&dir, &history);
                     cleanup_loop(&project_dir);
                     return;
                 }
+
+                // Back off further on rate limits, doubling per consecutive error
+                if kind == api_client::ApiErrorKind::RateLimited {
+                    let backoff = loop_interval.saturating_mul(1 << errors.min(5));
+                    append_log(&dir, &format!("Rate limited - waiting an extra {}s", backoff));
+                    sleep_with_stop_check(backoff, &stop_flag, &dir);
+                }
             }
         }
 
@@ -945,7 +2117,7 @@ fn run_loop(
         save_cycle_history(&dir, &history);
 
         // Sleep with periodic stop-flag checks
-        sleep_with_stop_check(loop_interval, &stop_flag);
+        sleep_with_stop_check(loop_interval, &stop_flag, &dir);
     }
 
     // Clean up on normal exit
@@ -954,14 +2126,107 @@ fn run_loop(
 
 // ===== API Cycle Execution =====
 
+/// Model for `agent_role`: its `AgentConfig.model` tier mapped through the
+/// provider's preset models, falling back to the loop-level model.
+fn agent_model(config: &FactoryConfig, agent_role: &str, credentials: &ApiCredentials) -> String {
+    let tier = match config.org.agents.iter().find(|a| a.role == agent_role).map(|a| &a.model) {
+        Some(ModelTier::Opus) => "opus",
+        Some(ModelTier::Sonnet) => "sonnet",
+        Some(ModelTier::Haiku) => "haiku",
+        None => return credentials.model.clone(),
+    };
+    crate::commands::provider_presets::model_for_tier(&credentials.engine_type, tier)
+        .unwrap_or_else(|| credentials.model.clone())
+}
+
+/// Paths skipped when tracking what a cycle changed: runtime bookkeeping and
+/// bulky directories that aren't the company's work.
+const UNTRACKED_PATHS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "target",
+    "logs",
+    ".loop.state",
+    ".loop.lock",
+    ".cycle_history.json",
+    ".load_balance.json",
+    "memories/events.json",
+    "memories/skill_wishlist.json",
+    "memories/notes.json",
+    "memories/consensus.md.bak",
+    "memories/consensus_history",
+];
+
+/// Upper bound on files recorded per snapshot, so huge workspaces stay cheap.
+const MAX_SNAPSHOT_FILES: usize = 20_000;
+
+/// Relative path -> (modified time, size) for every tracked file in the project.
+fn snapshot_project_files(dir: &Path) -> HashMap<String, (Option<std::time::SystemTime>, u64)> {
+    walk_project_files(dir, UNTRACKED_PATHS)
+}
+
+/// Relative path -> (modified time, size) for every file under `dir`, skipping
+/// the relative paths in `skip`.
+fn walk_project_files(dir: &Path, skip: &[&str]) -> HashMap<String, (Option<std::time::SystemTime>, u64)> {
+    let mut files = HashMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(rel) = path.strip_prefix(dir) else { continue };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if skip.contains(&rel.as_str()) {
+                continue;
+            }
+            let Ok(meta) = entry.metadata() else { continue };
+            if meta.is_dir() {
+                pending.push(path);
+            } else if files.len() < MAX_SNAPSHOT_FILES {
+                files.insert(rel, (meta.modified().ok(), meta.len()));
+            }
+        }
+    }
+    files
+}
+
+/// Paths added, modified or removed between two snapshots, sorted.
+fn diff_project_files(
+    before: &HashMap<String, (Option<std::time::SystemTime>, u64)>,
+    after: &HashMap<String, (Option<std::time::SystemTime>, u64)>,
+) -> Vec<String> {
+    let mut changed: Vec<String> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .chain(before.keys().filter(|path| !after.contains_key(*path)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// Run one agent turn and apply its results to the project. `cancelled` is
+/// checked before every write, so a cycle abandoned at its deadline stops
+/// touching shared files once the loop has moved on.
 fn run_api_cycle(
-    dir: &Path,
     project_dir: &str,
     credentials: &ApiCredentials,
+    config: &FactoryConfig,
+    prompt_ctx: &PromptContext,
     agent_role: &str,
     cycle: u32,
-    timeout_secs: u32,
-) -> Result<(String, u32, u32), String> {
+    cancelled: &AtomicBool,
+) -> Result<(String, u32, u32, Vec<String>), String> {
+    let dir = &PathBuf::from(project_dir);
+    let ensure_live = || {
+        if cancelled.load(Ordering::SeqCst) {
+            Err(format!("Cycle {} was abandoned after its deadline; discarding its results", cycle))
+        } else {
+            Ok(())
+        }
+    };
+    let before = snapshot_project_files(dir);
+
     // 1. Read agent file
     let agent_content = read_agent_file(dir, agent_role)?;
 
@@ -970,65 +2235,137 @@ fn run_api_cycle(
         .map_err(|e| format!("Failed to read consensus: {}", e))?;
 
     // 3. Load agent memory and handoff note from previous agent
-    let agent_memory = load_agent_memory(dir, agent_role);
+    let agent_memory = load_agent_memory(dir, agent_role, config.runtime.memory_window, config.runtime.memory_entry_chars);
     let handoff_note = load_handoff(dir);
+    let conversation = load_conversation(dir, agent_role, config.runtime.conversation_turns);
 
     // 4. Drain pending skill requests for injection
-    let injected_skills = drain_pending_skills(project_dir);
+    let injected_skills = drain_pending_skills(project_dir, agent_role);
 
     // 5. Build focused prompts with memory, handoff context, and injected skills
-    let system_prompt = build_system_prompt(&agent_content, agent_role, cycle, &agent_memory, &injected_skills);
+    let system_prompt = build_system_prompt(config, &agent_content, agent_role, cycle, &agent_memory, &injected_skills, prompt_ctx);
     let user_prompt = build_user_prompt(&consensus_content, &handoff_note);
+    let model = agent_model(config, agent_role, credentials);
 
     // 5. Call the appropriate API using unified ApiCallConfig
     let api_config = api_client::ApiCallConfig {
         api_key: credentials.api_key.clone(),
         api_base_url: credentials.api_base_url.clone(),
-        model: credentials.model.clone(),
+        model: model.clone(),
         system_prompt: system_prompt,
         user_message: user_prompt,
-        timeout_secs: timeout_secs,
+        timeout_secs: config.runtime.request_timeout,
         anthropic_version: credentials.anthropic_version.clone(),
         extra_headers: credentials.extra_headers.clone(),
+        provider_type: credentials.engine_type.clone(),
         force_stream: credentials.force_stream,
         api_format: if credentials.engine_type == "openai" {
             "openai".to_string()
         } else {
             credentials.api_format.clone()
         },
+        on_text_delta: APP_HANDLE.get().cloned().map(|app| {
+            let project_dir = project_dir.to_string();
+            let agent = agent_role.to_string();
+            Box::new(move |text: &str| {
+                let _ = app.emit("token-delta", TokenDelta {
+                    project_dir: project_dir.clone(),
+                    cycle,
+                    agent: agent.clone(),
+                    text: text.to_string(),
+                });
+            }) as api_client::TextDeltaCallback
+        }),
+        enable_prompt_cache: config.runtime.enable_prompt_cache,
+        history: conversation,
+        include_thinking: credentials.include_thinking,
+        extra_body: credentials.extra_body.clone(),
     };
 
     append_log(dir, &format!(
         "API call: engine={} model={} format={} stream={} url={}",
-        credentials.engine_type, credentials.model, api_config.api_format, api_config.force_stream, credentials.api_base_url,
+        credentials.engine_type, model, api_config.api_format, api_config.force_stream, credentials.api_base_url,
     ));
 
     let response = api_client::call_api(&api_config)?;
+    if response.truncated() {
+        append_log(dir, &format!(
+            "WARNING: Response truncated at max_tokens ({} output tokens) - raise max_tokens or trim the prompt",
+            response.output_tokens
+        ));
+    }
 
-    // 6. Try to extract and apply consensus update
-    if let Some(updated_consensus) = extract_consensus_update(&response.text) {
-        // Backup existing consensus
-        let backup_path = dir.join("memories/consensus.md.bak");
-        let _ = std::fs::copy(dir.join("memories/consensus.md"), &backup_path);
+    // 6. Check proposed shell commands against the project's guardrails
+    let violations = crate::engine::guardrails::check_response_commands(&response.text, &config.guardrails);
+    for violation in &violations {
+        append_log(dir, &format!("GUARDRAIL: {} agent proposed blocked command: {}", agent_role, violation));
+    }
+    if !violations.is_empty() {
+        emit_project_event(project_dir, "guardrail", agent_role, &format!("{} guardrail violation(s)", violations.len()), &violations.join("\n"));
+    }
+    let block_update = !violations.is_empty() && config.guardrails.block_unsafe_consensus;
+
+    // 7. Try to extract and apply consensus update
+    ensure_live()?;
+    if block_update {
+        append_log(dir, "Consensus update skipped due to guardrail violations");
+    } else if let Some((updated_consensus, missing)) = extract_consensus_update(&response.text, response.truncated(), &config.runtime.required_consensus_sections) {
+        if !missing.is_empty() && config.runtime.strict_consensus {
+            // Keep the rejected update around for inspection instead of discarding it
+            let _ = std::fs::write(dir.join("memories/consensus.rejected.md"), &updated_consensus);
+            append_log(dir, &format!(
+                "Consensus update rejected: missing sections [{}] (saved to memories/consensus.rejected.md)",
+                missing.join(", ")
+            ));
+        } else {
+            if !missing.is_empty() {
+                append_log(dir, &format!(
+                    "WARNING: Applying consensus update with missing sections [{}] (strict_consensus disabled)",
+                    missing.join(", ")
+                ));
+            }
 
-        // Write updated consensus
-        std::fs::write(dir.join("memories/consensus.md"), &updated_consensus)
-            .map_err(|e| format!("Failed to write consensus: {}", e))?;
+            // Backup, write and snapshot the updated consensus
+            crate::engine::memory::update_consensus(dir, &updated_consensus, cycle, true, &config.runtime.required_consensus_sections)?;
 
-        append_log(dir, &format!("Consensus updated by {} agent", agent_role));
+            for issue in crate::engine::memory::lint_markdown(&updated_consensus) {
+                append_log(dir, &format!("WARNING: consensus lint line {}: {}", issue.line, issue.message));
+            }
+
+            if response.truncated() && !response.text.contains("<<<CONSENSUS_END>>>") {
+                append_log(dir, "Consensus salvaged from truncated response up to the last complete section");
+            }
+            append_log(dir, &format!("Consensus updated by {} agent", agent_role));
+            emit_project_event(project_dir, "consensus_updated", agent_role, &format!("Consensus updated in cycle {}", cycle), "");
+        }
     } else {
         append_log(dir, "No structured consensus update in response (logged only)");
     }
 
-    // 7. Extract and save agent's reflection/memory and handoff note
+    ensure_live()?;
+    if config.runtime.conversation_turns > 0 {
+        save_conversation(
+            dir,
+            agent_role,
+            api_config.history.clone(),
+            &api_config.user_message,
+            &response.text,
+            config.runtime.conversation_turns,
+            config.runtime.conversation_message_chars,
+        );
+    }
+
+    // 8. Extract and save agent's reflection/memory and handoff note
     let reflection = extract_reflection(&response.text);
     let new_handoff = extract_handoff(&response.text);
 
+    ensure_live()?;
     if let Some(ref refl) = reflection {
         append_agent_memory(dir, agent_role, cycle, refl);
         append_log(dir, &format!("Agent {} saved reflection to memory", agent_role));
     }
 
+    ensure_live()?;
     if let Some(ref handoff) = new_handoff {
         save_handoff(dir, agent_role, cycle, handoff);
         append_log(dir, &format!("Agent {} left handoff note for next agent", agent_role));
@@ -1038,14 +2375,56 @@ fn run_api_cycle(
         save_handoff(dir, agent_role, cycle, &auto_handoff);
     }
 
-    // 8. Check for skill requests and queue them for the next cycle
+    // 9. Queue requested library skills for this agent's next turn; wishlist the rest
     let skill_requests = extract_skill_requests(&response.text);
+    ensure_live()?;
     if !skill_requests.is_empty() {
         append_log(dir, &format!("Agent {} requested skills: {}", agent_role, skill_requests.join(", ")));
-        queue_skill_requests(project_dir, &skill_requests);
+        let lib_dir = crate::commands::library::get_library_dir_pub();
+        let (available, missing): (Vec<String>, Vec<String>) = skill_requests
+            .iter()
+            .cloned()
+            .partition(|id| load_skill_full_content(id, lib_dir.as_deref()).is_some());
+        queue_skill_requests(project_dir, agent_role, &available);
+        if !missing.is_empty() {
+            append_log(dir, &format!("Skills not in library (added to wishlist): {}", missing.join(", ")));
+            record_skill_wishes(dir, agent_role, cycle, &missing);
+        }
+        emit_project_event(
+            project_dir,
+            "skill_requested",
+            agent_role,
+            &format!("Requested {} skill(s)", skill_requests.len()),
+            &skill_requests.join(", "),
+        );
     }
 
-    Ok((response.text, response.input_tokens, response.output_tokens))
+    let files_changed = diff_project_files(&before, &snapshot_project_files(dir));
+    Ok((response.text, response.input_tokens, response.output_tokens, files_changed))
+}
+
+/// Drop a leading `---` YAML frontmatter block from an agent file, keeping
+/// only its `description` (placed above the body).
+fn strip_agent_frontmatter(content: &str) -> String {
+    if !content.starts_with("---") {
+        return content.to_string();
+    }
+    let parts: Vec<&str> = content.splitn(3, "---").collect();
+    if parts.len() < 3 {
+        return content.to_string();
+    }
+
+    let description = parts[1]
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("description:"))
+        .map(|d| d.trim().trim_matches('"').trim_matches('\'').to_string())
+        .unwrap_or_default();
+    let body = parts[2].trim_start();
+    if description.is_empty() {
+        body.to_string()
+    } else {
+        format!("{}\n\n{}", description, body)
+    }
 }
 
 fn read_agent_file(dir: &Path, role: &str) -> Result<String, String> {
@@ -1057,6 +2436,7 @@ fn read_agent_file(dir: &Path, role: &str) -> Result<String, String> {
             let name = entry.file_name().to_string_lossy().to_string();
             if name.starts_with(&prefix) && name.ends_with(".md") {
                 return std::fs::read_to_string(entry.path())
+                    .map(|content| strip_agent_frontmatter(&content))
                     .map_err(|e| format!("Failed to read agent file: {}", e));
             }
         }
@@ -1069,9 +2449,34 @@ fn read_agent_file(dir: &Path, role: &str) -> Result<String, String> {
     ))
 }
 
-fn build_system_prompt(agent_content: &str, role: &str, cycle: u32, agent_memory: &str, injected_skills: &[String]) -> String {
-    // Load relevant skills for this agent's role
-    let skill_section = load_role_skills(role);
+fn build_system_prompt(
+    config: &FactoryConfig,
+    agent_content: &str,
+    role: &str,
+    cycle: u32,
+    agent_memory: &str,
+    injected_skills: &[String],
+    prompt_ctx: &PromptContext,
+) -> String {
+    // Company-wide rules come before the agent's own persona
+    let global_preamble = config.company.global_preamble.trim();
+    let preamble_section = if global_preamble.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n\n---\n\n", global_preamble)
+    };
+
+    // Load the agent's configured skills, or the role defaults
+    let configured_skills = config
+        .org
+        .agents
+        .iter()
+        .find(|a| a.role == role)
+        .map(|a| a.skills.as_slice())
+        .unwrap_or_default();
+    let skill_section = load_role_skills(role, configured_skills);
+    let mcp_tools = &prompt_ctx.mcp_tools;
+    let language_section = &prompt_ctx.language;
 
     // Build injected skills section from pending requests
     let injected_section = if injected_skills.is_empty() {
@@ -1102,8 +2507,8 @@ fn build_system_prompt(agent_content: &str, role: &str, cycle: u32, agent_memory
     };
 
     format!(
-        r#"{agent_content}
-{skill_section}{injected_section}{memory_section}
+        r#"{preamble_section}{agent_content}
+{skill_section}{mcp_tools}{injected_section}{memory_section}
 ---
 
 You are performing cycle {cycle} of the autonomous company loop.
@@ -1144,7 +2549,7 @@ RULES:
 - Preserve all existing sections
 - Be concise and actionable
 - Your reflection will be saved to your personal memory for future cycles
-- Your handoff note will be shown to the next agent in the chain"#,
+- Your handoff note will be shown to the next agent in the chain{language_section}"#,
         agent_content = agent_content,
         skill_section = skill_section,
         injected_section = injected_section,
@@ -1165,19 +2570,23 @@ fn build_user_prompt(consensus_content: &str, handoff_note: &str) -> String {
     }
 }
 
-fn extract_consensus_update(response: &str) -> Option<String> {
-    let content = extract_between_markers(response, "<<<CONSENSUS_START>>>", "<<<CONSENSUS_END>>>")?;
-
-    // Validate the extracted content has required consensus sections
-    if content.contains("## Company State")
-        && content.contains("## Current Focus")
-        && content.contains("## Decision Log")
-        && content.len() > 100
-    {
-        Some(content)
-    } else {
-        None
+/// Extract the consensus block from a response together with the `required`
+/// sections it is missing. Returns None when there is no usable block at all.
+/// For a truncated response whose end marker never arrived, the complete
+/// sections before the cut are salvaged.
+fn extract_consensus_update(response: &str, truncated: bool, required: &[String]) -> Option<(String, Vec<String>)> {
+    let content = extract_between_markers(response, "<<<CONSENSUS_START>>>", "<<<CONSENSUS_END>>>")
+        .or_else(|| truncated.then(|| salvage_truncated_consensus(response)).flatten())?;
+    if content.len() <= 100 {
+        return None;
     }
+
+    let missing = required
+        .iter()
+        .filter(|section| !section.trim().is_empty() && !content.contains(section.trim()))
+        .cloned()
+        .collect();
+    Some((content, missing))
 }
 
 // ===== Engine Binary Resolution (used by system.rs) =====
@@ -1239,13 +2648,25 @@ pub fn find_binary(name: &str) -> Option<String> {
 
 // ===== Helper Functions =====
 
-fn load_project_config(dir: &Path) -> Result<FactoryConfig, String> {
+pub(crate) fn load_project_config(dir: &Path) -> Result<FactoryConfig, String> {
     let config_path = dir.join("company.yaml");
     let content = std::fs::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read company.yaml: {}", e))?;
     serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse company.yaml: {}", e))
 }
 
+fn save_project_config(dir: &Path, config: &FactoryConfig) -> Result<(), String> {
+    let yaml = serde_yaml::to_string(config)
+        .map_err(|e| format!("Failed to serialize company.yaml: {}", e))?;
+    std::fs::write(dir.join("company.yaml"), yaml)
+        .map_err(|e| format!("Failed to write company.yaml: {}", e))
+}
+
+/// Total cost of the cycles that completed on `day` (`%Y-%m-%d`).
+fn daily_spend(history: &[CycleResult], day: &str) -> f64 {
+    history.iter().filter(|c| c.completed_at.starts_with(day)).map(|c| c.cost_usd).sum()
+}
+
 fn load_cycle_history(dir: &Path) -> Vec<CycleResult> {
     let path = dir.join(".cycle_history.json");
     std::fs::read_to_string(&path)
@@ -1268,15 +2689,52 @@ fn write_state(
     total: u32,
     errors: u32,
 ) -> Result<(), String> {
-    let timestamp = chrono::Local::now().format("%+").to_string();
-    let content = format!(
+    let now = chrono::Local::now();
+    let timestamp = now.format("%+").to_string();
+
+    // Carry session timestamps across rewrites: a session starts when the
+    // state goes to running and ends on the first non-running write after it.
+    let state_file = dir.join(".loop.state");
+    let previous = std::fs::read_to_string(&state_file).unwrap_or_default();
+    let was_running = previous.contains("status=running");
+    let (prev_started, prev_stopped) = parse_session_times(&previous);
+    let (started, stopped) = if status == "running" {
+        (if was_running { prev_started.or(Some(now.timestamp())) } else { Some(now.timestamp()) }, None)
+    } else if was_running {
+        (prev_started, Some(now.timestamp()))
+    } else {
+        (prev_started, prev_stopped)
+    };
+
+    let mut content = format!(
         "current_cycle={}\ntotal_cycles={}\nconsecutive_errors={}\nstatus={}\nlast_cycle_at={}\n",
         cycle, total, errors, status, timestamp
     );
-    std::fs::write(dir.join(".loop.state"), content)
+    if let Some(epoch) = started {
+        content.push_str(&format!("started_at_epoch={}\n", epoch));
+    }
+    if let Some(epoch) = stopped {
+        content.push_str(&format!("stopped_at_epoch={}\n", epoch));
+    }
+    std::fs::write(state_file, content)
         .map_err(|e| format!("Failed to write state: {}", e))
 }
 
+/// Read (started_at_epoch, stopped_at_epoch) from `.loop.state` content.
+fn parse_session_times(content: &str) -> (Option<i64>, Option<i64>) {
+    let mut started = None;
+    let mut stopped = None;
+    for line in content.lines() {
+        if let Some(val) = line.strip_prefix("started_at_epoch=") {
+            started = val.parse().ok();
+        }
+        if let Some(val) = line.strip_prefix("stopped_at_epoch=") {
+            stopped = val.parse().ok();
+        }
+    }
+    (started, stopped)
+}
+
 fn parse_state_file(state_file: &Path) -> (u32, u32, u32, Option<String>) {
     let content = std::fs::read_to_string(state_file).unwrap_or_default();
     let mut cc = 0u32;
@@ -1322,18 +2780,154 @@ fn cleanup_loop(project_dir: &str) {
     if let Ok(mut loops) = RUNNING_LOOPS.lock() {
         loops.remove(project_dir);
     }
+    release_loop_lock(Path::new(project_dir));
+}
+
+// ===== Cross-Process Loop Lock =====
+
+const LOOP_LOCK_FILE: &str = ".loop.lock";
+/// Slack on top of `cycle_timeout` before an unrefreshed lock counts as stale.
+const LOOP_LOCK_GRACE_SECS: i64 = 120;
+/// How often a sleeping loop refreshes its lock.
+const LOOP_LOCK_REFRESH_SECS: u32 = 30;
+
+fn loop_lock_contents() -> String {
+    format!("pid={}\ntimestamp={}\n", std::process::id(), chrono::Utc::now().timestamp())
+}
+
+/// (pid, unix timestamp) from a `.loop.lock` file.
+fn parse_loop_lock(content: &str) -> (Option<u32>, Option<i64>) {
+    let field = |key: &str| {
+        content
+            .lines()
+            .find_map(|l| l.strip_prefix(key).and_then(|v| v.strip_prefix('=')))
+            .map(str::trim)
+    };
+    (
+        field("pid").and_then(|v| v.parse().ok()),
+        field("timestamp").and_then(|v| v.parse().ok()),
+    )
+}
+
+/// Take the project's `.loop.lock`, refusing while another process holds a
+/// lock refreshed within `stale_after_secs`. Stale locks, and leftovers from
+/// this process, are replaced.
+fn acquire_loop_lock(dir: &Path, stale_after_secs: i64) -> Result<(), String> {
+    let path = dir.join(LOOP_LOCK_FILE);
+    if let Ok(existing) = std::fs::read_to_string(&path) {
+        let (pid, timestamp) = parse_loop_lock(&existing);
+        let age = chrono::Utc::now().timestamp() - timestamp.unwrap_or(0);
+        let own = pid == Some(std::process::id());
+        if !own && (0..stale_after_secs).contains(&age) {
+            return Err(foreign_lock_message(pid, age));
+        }
+        let _ = std::fs::remove_file(&path);
+        if !own {
+            append_log(dir, &format!("Removed stale {} ({}s old)", LOOP_LOCK_FILE, age));
+        }
+    }
+
+    use std::io::Write;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(loop_lock_contents().as_bytes()))
+        .map_err(|e| format!("Failed to create {}: {}", LOOP_LOCK_FILE, e))
+}
+
+/// Err while another process holds a fresh `.loop.lock` on the project, using
+/// the same staleness window as `launch_loop`.
+fn check_foreign_loop_lock(dir: &Path, config: &FactoryConfig) -> Result<(), String> {
+    let Ok(existing) = std::fs::read_to_string(dir.join(LOOP_LOCK_FILE)) else {
+        return Ok(());
+    };
+    let (pid, timestamp) = parse_loop_lock(&existing);
+    let age = chrono::Utc::now().timestamp() - timestamp.unwrap_or(0);
+    let stale_after = config.runtime.cycle_timeout as i64 + LOOP_LOCK_GRACE_SECS;
+    if pid != Some(std::process::id()) && (0..stale_after).contains(&age) {
+        return Err(foreign_lock_message(pid, age));
+    }
+    Ok(())
+}
+
+/// Whether a loop is running on `dir`, in this process or, going by a fresh
+/// `.loop.lock`, in another one.
+pub(crate) fn loop_is_active(dir: &Path) -> bool {
+    let running_here = RUNNING_LOOPS
+        .lock()
+        .map(|loops| loops.iter().any(|(key, flag)| Path::new(key) == dir && !flag.load(Ordering::Relaxed)))
+        .unwrap_or(false);
+    running_here || load_project_config(dir).is_ok_and(|config| check_foreign_loop_lock(dir, &config).is_err())
+}
+
+fn foreign_lock_message(pid: Option<u32>, age: i64) -> String {
+    format!(
+        "Another process (pid {}) is running a loop on this project; lock refreshed {}s ago",
+        pid.map_or_else(|| "?".to_string(), |p| p.to_string()),
+        age
+    )
+}
+
+fn refresh_loop_lock(dir: &Path) {
+    let _ = std::fs::write(dir.join(LOOP_LOCK_FILE), loop_lock_contents());
+}
+
+/// Remove the lock if this process holds it.
+fn release_loop_lock(dir: &Path) {
+    let path = dir.join(LOOP_LOCK_FILE);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if parse_loop_lock(&content).0 == Some(std::process::id()) {
+        let _ = std::fs::remove_file(&path);
+    }
+}
+
+/// Why `run_with_deadline` returned without a result.
+enum DeadlineError {
+    Timeout,
+    /// The worker panicked; carries the panic message
+    Panicked(String),
+}
+
+/// Run `work` on its own thread and wait at most `timeout_secs` for it.
+/// On timeout the worker is abandoned and left to finish in the background;
+/// callers must signal it to stop writing (see `run_api_cycle`).
+fn run_with_deadline<T: Send + 'static>(
+    timeout_secs: u32,
+    work: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, DeadlineError> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    thread::spawn(move || {
+        let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(work)).map_err(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string())
+        });
+        let _ = tx.send(outcome);
+    });
+    match rx.recv_timeout(Duration::from_secs(timeout_secs as u64)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(message)) => Err(DeadlineError::Panicked(message)),
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(DeadlineError::Timeout),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+            Err(DeadlineError::Panicked("worker exited without a result".to_string()))
+        }
+    }
 }
 
-fn sleep_with_stop_check(seconds: u32, stop_flag: &Arc<AtomicBool>) {
-    let total = Duration::from_secs(seconds as u64);
-    let check = Duration::from_secs(1);
-    let mut elapsed = Duration::ZERO;
-    while elapsed < total {
+/// Sleep in one-second steps until `seconds` pass or the loop is stopped,
+/// keeping the project's loop lock fresh meanwhile.
+fn sleep_with_stop_check(seconds: u32, stop_flag: &Arc<AtomicBool>, dir: &Path) {
+    for elapsed in 0..seconds {
         if stop_flag.load(Ordering::Relaxed) {
             break;
         }
-        thread::sleep(check);
-        elapsed += check;
+        if elapsed > 0 && elapsed % LOOP_LOCK_REFRESH_SECS == 0 {
+            refresh_loop_lock(dir);
+        }
+        thread::sleep(Duration::from_secs(1));
     }
 }
 
@@ -1341,14 +2935,18 @@ fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len])
+        let mut end = max_len;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &s[..end])
     }
 }
 
 // ===== Workspace-as-Memory (inspired by nanobot) =====
 
 /// Load the last N reflections from an agent's personal memory file.
-fn load_agent_memory(dir: &Path, role: &str) -> String {
+fn load_agent_memory(dir: &Path, role: &str, window: usize, entry_chars: usize) -> String {
     let memory_path = dir.join(format!("memories/agents/{}/MEMORY.md", role));
     if !memory_path.exists() {
         return String::new();
@@ -1356,10 +2954,15 @@ fn load_agent_memory(dir: &Path, role: &str) -> String {
 
     match std::fs::read_to_string(&memory_path) {
         Ok(content) => {
-            // Return only the last 5 entries to keep context manageable
+            // Return only the last `window` entries to keep context manageable,
+            // each capped so one oversized reflection can't blow the prompt
             let entries: Vec<&str> = content.split("\n---\n").collect();
-            let start = if entries.len() > 5 { entries.len() - 5 } else { 0 };
-            entries[start..].join("\n---\n")
+            let start = entries.len().saturating_sub(window);
+            entries[start..]
+                .iter()
+                .map(|e| truncate_string(e, entry_chars))
+                .collect::<Vec<_>>()
+                .join("\n---\n")
         }
         Err(_) => String::new(),
     }
@@ -1388,6 +2991,49 @@ fn append_agent_memory(dir: &Path, role: &str, cycle: u32, reflection: &str) {
     }
 }
 
+/// Load the last `turns` user/assistant pairs from the agent's conversation file.
+fn load_conversation(dir: &Path, role: &str, turns: usize) -> Vec<api_client::ApiMessage> {
+    if turns == 0 {
+        return Vec::new();
+    }
+    let path = dir.join(format!("memories/agents/{}/conversation.json", role));
+    let messages: Vec<api_client::ApiMessage> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let start = messages.len().saturating_sub(turns * 2);
+    messages[start..].to_vec()
+}
+
+/// Append this cycle's exchange to the agent's conversation file, keeping the
+/// last `turns` pairs. Each message is capped at `message_chars` since the
+/// user turn carries the whole consensus document.
+fn save_conversation(
+    dir: &Path,
+    role: &str,
+    mut messages: Vec<api_client::ApiMessage>,
+    user_prompt: &str,
+    response: &str,
+    turns: usize,
+    message_chars: usize,
+) {
+    messages.push(api_client::ApiMessage {
+        role: "user".to_string(),
+        content: truncate_string(user_prompt, message_chars),
+    });
+    messages.push(api_client::ApiMessage {
+        role: "assistant".to_string(),
+        content: truncate_string(response, message_chars),
+    });
+    let start = messages.len().saturating_sub(turns * 2);
+
+    let memory_dir = dir.join(format!("memories/agents/{}", role));
+    let _ = std::fs::create_dir_all(&memory_dir);
+    if let Ok(json) = serde_json::to_string_pretty(&messages[start..]) {
+        let _ = std::fs::write(memory_dir.join("conversation.json"), json);
+    }
+}
+
 /// Load the handoff note left by the previous agent.
 fn load_handoff(dir: &Path) -> String {
     let handoff_path = dir.join("memories/HANDOFF.md");
@@ -1402,7 +3048,18 @@ fn save_handoff(dir: &Path, from_role: &str, cycle: u32, note: &str) {
         "**From: {} | Cycle {} | {}**\n\n{}",
         from_role, cycle, timestamp, note
     );
-    let _ = std::fs::write(handoff_path, content);
+    let _ = std::fs::write(handoff_path, &content);
+
+    // Keep the full chain in HANDOFF_LOG.md; HANDOFF.md stays the latest pointer
+    let log_entry = format!("\n---\n## {} | {} | Cycle {}\n\n{}\n", timestamp, from_role, cycle, note);
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join("memories/HANDOFF_LOG.md"))
+    {
+        use std::io::Write;
+        let _ = file.write_all(log_entry.as_bytes());
+    }
 }
 
 // ===== Reflection/Handoff Extraction =====
@@ -1421,6 +3078,16 @@ fn extract_handoff(response: &str) -> Option<String> {
     extract_between_markers(response, start, end)
 }
 
+/// Consensus text after an unterminated start marker, cut before the last
+/// `## ` heading since that section was likely interrupted.
+fn salvage_truncated_consensus(response: &str) -> Option<String> {
+    let start = response.find("<<<CONSENSUS_START>>>")? + "<<<CONSENSUS_START>>>".len();
+    let partial = &response[start..];
+    let cut = partial.rfind("\n## ")?;
+    let content = partial[..cut].trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
 /// Generic marker extraction helper.
 fn extract_between_markers(text: &str, start_marker: &str, end_marker: &str) -> Option<String> {
     let start_idx = text.find(start_marker)?;
@@ -1436,46 +3103,197 @@ fn extract_between_markers(text: &str, start_marker: &str, end_marker: &str) ->
 
 // ===== Phase 3: Skill Injection =====
 
-/// Map agent role to relevant skill IDs for context injection.
-fn role_to_skills(role: &str) -> Vec<&'static str> {
-    match role {
-        "ceo" => vec!["deep-research", "product-strategist", "market-sizing", "startup-financial-modeling", "premortem"],
-        "fullstack" => vec!["code-review-security", "tdd-workflow", "frontend-patterns", "backend-patterns", "api-design"],
-        "devops" => vec!["devops", "docker-patterns", "security-audit", "deployment-patterns"],
-        "critic" => vec!["premortem", "financial-unit-economics", "security-review"],
-        "product" => vec!["product-strategist", "deep-research", "market-sizing"],
-        "ui" => vec!["frontend-patterns", "product-strategist"],
-        "qa" => vec!["senior-qa", "tdd-workflow", "e2e-testing", "verification-loop"],
-        "marketing" => vec!["seo-content-strategist", "competitive-intelligence", "content-strategy"],
-        "operations" => vec!["micro-saas-launcher", "startup-financial-modeling"],
-        "sales" => vec!["competitive-intelligence", "pricing-strategy"],
-        "cfo" => vec!["financial-unit-economics", "pricing-strategy", "startup-financial-modeling"],
-        "research" => vec!["deep-research", "competitive-intelligence", "market-sizing"],
-        _ => vec![],
+/// Built-in role -> skill IDs used for context injection when
+/// `library/role_skills.yaml` doesn't list the role.
+const DEFAULT_ROLE_SKILLS: &[(&str, &[&str])] = &[
+    ("ceo", &["deep-research", "product-strategist", "market-sizing", "startup-financial-modeling", "premortem"]),
+    ("fullstack", &["code-review-security", "tdd-workflow", "frontend-patterns", "backend-patterns", "api-design"]),
+    ("devops", &["devops", "docker-patterns", "security-audit", "deployment-patterns"]),
+    ("critic", &["premortem", "financial-unit-economics", "security-review"]),
+    ("product", &["product-strategist", "deep-research", "market-sizing"]),
+    ("ui", &["frontend-patterns", "product-strategist"]),
+    ("qa", &["senior-qa", "tdd-workflow", "e2e-testing", "verification-loop"]),
+    ("marketing", &["seo-content-strategist", "competitive-intelligence", "content-strategy"]),
+    ("operations", &["micro-saas-launcher", "startup-financial-modeling"]),
+    ("sales", &["competitive-intelligence", "pricing-strategy"]),
+    ("cfo", &["financial-unit-economics", "pricing-strategy", "startup-financial-modeling"]),
+    ("research", &["deep-research", "competitive-intelligence", "market-sizing"]),
+];
+
+const ROLE_SKILLS_FILE: &str = "role_skills.yaml";
+
+/// The role -> skill IDs mapping from `role_skills.yaml` in the library, if any.
+fn load_role_skills_file(lib_dir: Option<&Path>) -> Option<HashMap<String, Vec<String>>> {
+    let content = std::fs::read_to_string(lib_dir?.join(ROLE_SKILLS_FILE)).ok()?;
+    serde_yaml::from_str(&content).ok()
+}
+
+/// Map agent role to relevant skill IDs for context injection: the library's
+/// `role_skills.yaml` entry when present, else the built-in defaults.
+fn role_to_skills(role: &str, lib_dir: Option<&Path>) -> Vec<String> {
+    if let Some(skills) = load_role_skills_file(lib_dir).and_then(|mut map| map.remove(role)) {
+        return skills;
     }
+    DEFAULT_ROLE_SKILLS
+        .iter()
+        .find(|(r, _)| *r == role)
+        .map(|(_, skills)| skills.iter().map(|s| s.to_string()).collect())
+        .unwrap_or_default()
 }
 
-/// Load skill summaries for a given role and format as a prompt section.
-fn load_role_skills(role: &str) -> String {
-    let skill_ids = role_to_skills(role);
+/// The effective role -> skill IDs mapping: built-in defaults overlaid with
+/// `library/role_skills.yaml`.
+#[command]
+pub fn get_role_skills() -> Result<HashMap<String, Vec<String>>, String> {
+    let mut mapping: HashMap<String, Vec<String>> = DEFAULT_ROLE_SKILLS
+        .iter()
+        .map(|(role, skills)| (role.to_string(), skills.iter().map(|s| s.to_string()).collect()))
+        .collect();
+    let lib_dir = crate::commands::library::get_library_dir_pub();
+    mapping.extend(load_role_skills_file(lib_dir.as_deref()).unwrap_or_default());
+    Ok(mapping)
+}
+
+/// Write the role -> skill IDs mapping to `library/role_skills.yaml`. Roles
+/// left out fall back to the built-in defaults.
+#[command]
+pub fn save_role_skills(mapping: HashMap<String, Vec<String>>) -> Result<bool, String> {
+    let lib_dir = crate::commands::library::get_library_dir_pub()
+        .ok_or_else(|| "Library directory not found".to_string())?;
+    let sorted: std::collections::BTreeMap<_, _> = mapping.into_iter().collect();
+    let yaml = serde_yaml::to_string(&sorted)
+        .map_err(|e| format!("Failed to serialize role skills: {}", e))?;
+    std::fs::write(lib_dir.join(ROLE_SKILLS_FILE), yaml)
+        .map_err(|e| format!("Failed to write {}: {}", ROLE_SKILLS_FILE, e))?;
+    Ok(true)
+}
+
+/// Resolve the skill sections injected for a role as (skill_id, section) pairs.
+/// An agent's own `skills` list, when non-empty, replaces the role defaults.
+/// Skills disabled in the library are left out.
+fn role_skill_sections(role: &str, configured: &[String]) -> Vec<(String, String)> {
+    let lib_dir = crate::commands::library::get_library_dir_pub();
+    let disabled = crate::commands::library::get_library_state()
+        .map(|state| state.disabled_skills)
+        .unwrap_or_default();
+    let skill_ids: Vec<String> = if configured.is_empty() {
+        role_to_skills(role, lib_dir.as_deref())
+    } else {
+        configured.to_vec()
+    }
+    .into_iter()
+    .filter(|id| !disabled.contains(id))
+    .collect();
     if skill_ids.is_empty() {
-        return String::new();
+        return Vec::new();
     }
 
-    let lib_dir = crate::commands::library::get_library_dir_pub();
     let mut skill_sections = Vec::new();
 
     for skill_id in &skill_ids {
         if let Some(summary) = load_skill_summary(skill_id, lib_dir.as_deref()) {
-            skill_sections.push(format!("### {}\n{}", skill_id, summary));
+            skill_sections.push((skill_id.clone(), format!("### {}\n{}", skill_id, summary)));
         }
     }
 
+    skill_sections
+}
+
+/// Load skill summaries for a given role and format as a prompt section.
+fn load_role_skills(role: &str, configured: &[String]) -> String {
+    let skill_sections = role_skill_sections(role, configured);
     if skill_sections.is_empty() {
         return String::new();
     }
 
-    format!("\n\n## Available Skills\n\n{}", skill_sections.join("\n\n"))
+    let sections: Vec<&str> = skill_sections.iter().map(|(_, s)| s.as_str()).collect();
+    format!("\n\n## Available Skills\n\n{}", sections.join("\n\n"))
+}
+
+/// The "## Available Skills" section exactly as it would be injected into the
+/// system prompt for `role`, resolved against the current library. With a
+/// `project_dir`, the agent's configured `skills` are used as in the loop.
+/// Empty when the role maps to no skills or none of them can be found.
+#[command]
+pub fn preview_role_skills(role: String, project_dir: Option<String>) -> Result<String, String> {
+    let configured = match project_dir.filter(|d| !d.is_empty()) {
+        Some(project_dir) => load_project_config(&PathBuf::from(project_dir))?
+            .org
+            .agents
+            .into_iter()
+            .find(|a| a.role == role)
+            .map(|a| a.skills)
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    Ok(load_role_skills(&role, &configured).trim_start().to_string())
+}
+
+/// Prompt sections that come from app settings rather than the project,
+/// shared by every agent in a loop.
+#[derive(Clone, Default)]
+struct PromptContext {
+    mcp_tools: String,
+    language: String,
+}
+
+fn load_prompt_context() -> PromptContext {
+    match crate::commands::settings::load_settings() {
+        Ok(settings) => PromptContext {
+            mcp_tools: mcp_tools_section(&settings.mcp_servers),
+            language: language_section(&settings.language),
+        },
+        Err(_) => PromptContext::default(),
+    }
+}
+
+/// Instruction to answer in the app's configured language. Empty for English.
+/// Unknown codes are passed through as the language name.
+fn language_section(code: &str) -> String {
+    let language = match code.trim().to_lowercase().as_str() {
+        "" | "en" => return String::new(),
+        "es" => "Spanish",
+        "fr" => "French",
+        "de" => "German",
+        "ja" => "Japanese",
+        "zh" | "zh-cn" => "Simplified Chinese",
+        "zh-tw" => "Traditional Chinese",
+        "pt" => "Portuguese",
+        "ko" => "Korean",
+        _ => code.trim(),
+    };
+    format!(
+        "\n\nLANGUAGE:\n- Respond in {language}, including the consensus content, reflection and handoff note\n\
+         - Keep the <<<...>>> markers and the existing consensus section headings exactly as they are"
+    )
+}
+
+/// Build the "Available MCP Tools" prompt section from the tools cached on
+/// enabled MCP servers. Empty when nothing has been discovered.
+fn mcp_tools_section(servers: &[McpServerConfig]) -> String {
+    let sections: Vec<String> = servers
+        .iter()
+        .filter(|s| s.enabled && !s.tools.is_empty())
+        .map(|s| {
+            let tools: Vec<String> = s
+                .tools
+                .iter()
+                .map(|t| {
+                    if t.description.is_empty() {
+                        format!("- `{}`", t.name)
+                    } else {
+                        format!("- `{}`: {}", t.name, t.description.lines().next().unwrap_or("").trim())
+                    }
+                })
+                .collect();
+            format!("### {}\n{}", s.name, tools.join("\n"))
+        })
+        .collect();
+
+    if sections.is_empty() {
+        return String::new();
+    }
+    format!("\n\n## Available MCP Tools\n\n{}", sections.join("\n\n"))
 }
 
 /// Load a brief summary of a skill from disk.
@@ -1596,10 +3414,13 @@ fn extract_skill_requests(response: &str) -> Vec<String> {
     requests
 }
 
-/// Queue skill requests for injection in the next cycle.
-fn queue_skill_requests(project_dir: &str, skill_ids: &[String]) {
+/// Queue skill requests for injection the next time `role` runs.
+fn queue_skill_requests(project_dir: &str, role: &str, skill_ids: &[String]) {
+    if skill_ids.is_empty() {
+        return;
+    }
     if let Ok(mut map) = PENDING_SKILL_REQUESTS.lock() {
-        let entry = map.entry(project_dir.to_string()).or_default();
+        let entry = map.entry((project_dir.to_string(), role.to_string())).or_default();
         for id in skill_ids {
             if !entry.contains(id) {
                 entry.push(id.clone());
@@ -1608,15 +3429,60 @@ fn queue_skill_requests(project_dir: &str, skill_ids: &[String]) {
     }
 }
 
-/// Drain pending skill requests (returns and clears them).
-fn drain_pending_skills(project_dir: &str) -> Vec<String> {
+/// The skills queued for an agent's next turn, left in the queue.
+fn peek_pending_skills(project_dir: &str, role: &str) -> Vec<String> {
+    PENDING_SKILL_REQUESTS
+        .lock()
+        .ok()
+        .and_then(|map| map.get(&(project_dir.to_string(), role.to_string())).cloned())
+        .unwrap_or_default()
+}
+
+/// Drain pending skill requests for `role` (returns and clears them).
+fn drain_pending_skills(project_dir: &str, role: &str) -> Vec<String> {
     if let Ok(mut map) = PENDING_SKILL_REQUESTS.lock() {
-        map.remove(project_dir).unwrap_or_default()
+        map.remove(&(project_dir.to_string(), role.to_string())).unwrap_or_default()
     } else {
         Vec::new()
     }
 }
 
+fn load_skill_wishlist(dir: &Path) -> Vec<SkillWish> {
+    std::fs::read_to_string(dir.join("memories/skill_wishlist.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Add requested skills that the library lacks to `memories/skill_wishlist.json`.
+fn record_skill_wishes(dir: &Path, role: &str, cycle: u32, skill_ids: &[String]) {
+    let now = chrono::Local::now().format("%+").to_string();
+    let mut wishlist = load_skill_wishlist(dir);
+    for id in skill_ids {
+        match wishlist.iter_mut().find(|w| &w.skill_id == id) {
+            Some(wish) => {
+                wish.count += 1;
+                wish.last_requested = now.clone();
+                wish.last_cycle = cycle;
+                if !wish.requested_by.iter().any(|r| r == role) {
+                    wish.requested_by.push(role.to_string());
+                }
+            }
+            None => wishlist.push(SkillWish {
+                skill_id: id.clone(),
+                requested_by: vec![role.to_string()],
+                count: 1,
+                first_requested: now.clone(),
+                last_requested: now.clone(),
+                last_cycle: cycle,
+            }),
+        }
+    }
+    if let Ok(json) = serde_json::to_string_pretty(&wishlist) {
+        let _ = std::fs::write(dir.join("memories/skill_wishlist.json"), json);
+    }
+}
+
 /// Load the full content of a skill from disk (for injection when requested).
 fn load_skill_full_content(skill_id: &str, lib_dir: Option<&std::path::Path>) -> Option<String> {
     let lib = lib_dir?;
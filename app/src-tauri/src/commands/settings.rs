@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 use tauri::command;
 use crate::models::*;
-use crate::engine::api_client;
+use crate::engine::{self, api_client};
+use crate::commands::provider_presets::{get_provider_presets, ModelOption};
 
 fn get_settings_path() -> PathBuf {
     dirs::data_dir()
@@ -10,8 +11,12 @@ fn get_settings_path() -> PathBuf {
         .join("settings.json")
 }
 
+/// Current `AppSettings::schema_version`. Bump when adding a migration step.
+pub const SETTINGS_SCHEMA_VERSION: u32 = 2;
+
 fn default_settings() -> AppSettings {
     AppSettings {
+        schema_version: SETTINGS_SCHEMA_VERSION,
         default_engine: "claude".to_string(),
         default_model: "sonnet".to_string(),
         max_daily_budget: 50.0,
@@ -29,9 +34,29 @@ fn default_settings() -> AppSettings {
         theme: "obsidian".to_string(),
         mcp_servers: vec![],
         skill_repos: vec![],
+        library_dir: String::new(),
+        role_persona_overrides: std::collections::HashMap::new(),
+        github_token: String::new(),
+        github_cache_ttl_secs: 600,
+        proxy_url: String::new(),
+        model_pricing: default_model_pricing(),
+        webhook_url: String::new(),
+        default_provider_ids: std::collections::HashMap::new(),
     }
 }
 
+/// Built-in rates for every preset model, used to seed `model_pricing`.
+fn default_model_pricing() -> std::collections::HashMap<String, ModelPricing> {
+    crate::commands::provider_presets::get_provider_presets()
+        .into_iter()
+        .flat_map(|preset| preset.models)
+        .map(|m| {
+            let (input_per_mtok, output_per_mtok) = engine::cost::model_rates(&m.id);
+            (m.id, ModelPricing { input_per_mtok, output_per_mtok })
+        })
+        .collect()
+}
+
 #[command]
 pub fn load_settings() -> Result<AppSettings, String> {
     let path = get_settings_path();
@@ -44,18 +69,85 @@ pub fn load_settings() -> Result<AppSettings, String> {
         let json = serde_json::to_string_pretty(&settings)
             .map_err(|e| format!("Serialize error: {}", e))?;
         let _ = std::fs::write(&path, &json);
+        engine::cost::set_pricing_overrides(&settings.model_pricing);
         return Ok(settings);
     }
 
     let content = std::fs::read_to_string(&path)
         .map_err(|e| format!("Failed to read settings: {}", e))?;
-    let settings: AppSettings = serde_json::from_str(&content)
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Parse error: {}", e))?;
+    let migrated = migrate_settings(&mut value);
+    let settings: AppSettings = serde_json::from_value(value)
         .map_err(|e| format!("Parse error: {}", e))?;
+    if migrated {
+        // Best effort, like the defaults above: a failed write-back must not
+        // stop the migrated settings from loading
+        if let Ok(json) = serde_json::to_string_pretty(&settings) {
+            let _ = std::fs::write(&path, json);
+        }
+    }
+    engine::http::set_proxy_url(&settings.proxy_url);
+    engine::cost::set_pricing_overrides(&settings.model_pricing);
+    engine::webhook::set_webhook_url(&settings.webhook_url);
     Ok(settings)
 }
 
+/// Upgrade a raw settings document to `SETTINGS_SCHEMA_VERSION` in place.
+/// Returns true if anything changed and the file should be rewritten.
+pub fn migrate_settings(value: &mut serde_json::Value) -> bool {
+    let Some(obj) = value.as_object_mut() else {
+        return false;
+    };
+    let version = obj.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if version >= SETTINGS_SCHEMA_VERSION {
+        return false;
+    }
+
+    // v0 -> v1: fill fields older files lack and that have no usable serde default
+    if version < 1 {
+        if let Some(providers) = obj.get_mut("providers").and_then(|p| p.as_array_mut()) {
+            for provider in providers.iter_mut().filter_map(|p| p.as_object_mut()) {
+                // Missing api_format would default to "anthropic"; derive it from the type instead
+                if !provider.contains_key("api_format") {
+                    let provider_type = provider.get("provider_type").and_then(|t| t.as_str()).unwrap_or_default();
+                    let (api_format, _) = derive_api_config(provider_type);
+                    provider.insert("api_format".into(), api_format.into());
+                }
+                provider.entry("is_healthy").or_insert(true.into());
+                provider.entry("last_error").or_insert(serde_json::Value::Null);
+            }
+        }
+        if let Some(servers) = obj.get_mut("mcp_servers").and_then(|s| s.as_array_mut()) {
+            for server in servers.iter_mut().filter_map(|s| s.as_object_mut()) {
+                server.entry("command").or_insert("".into());
+                server.entry("args").or_insert(serde_json::json!([]));
+                server.entry("url").or_insert("".into());
+                server.entry("env").or_insert(serde_json::json!({}));
+                server.entry("enabled").or_insert(true.into());
+                server.entry("tools").or_insert(serde_json::json!([]));
+            }
+        }
+    }
+
+    // v1 -> v2: seed the pricing table with the preset models' built-in rates
+    if version < 2 && !obj.contains_key("model_pricing") {
+        let pricing = serde_json::to_value(default_model_pricing()).unwrap_or_default();
+        obj.insert("model_pricing".into(), pricing);
+    }
+
+    obj.insert("schema_version".into(), SETTINGS_SCHEMA_VERSION.into());
+    true
+}
+
 #[command]
 pub fn save_settings(settings: AppSettings) -> Result<bool, String> {
+    if !settings.proxy_url.trim().is_empty() {
+        engine::http::validate_proxy_url(&settings.proxy_url)?;
+    }
+    if !settings.webhook_url.trim().is_empty() {
+        engine::webhook::validate_webhook_url(&settings.webhook_url)?;
+    }
     let path = get_settings_path();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
@@ -65,9 +157,59 @@ pub fn save_settings(settings: AppSettings) -> Result<bool, String> {
         .map_err(|e| format!("Serialize error: {}", e))?;
     std::fs::write(&path, &json)
         .map_err(|e| format!("Write error: {}", e))?;
+    engine::http::set_proxy_url(&settings.proxy_url);
+    engine::cost::set_pricing_overrides(&settings.model_pricing);
+    engine::webhook::set_webhook_url(&settings.webhook_url);
     Ok(true)
 }
 
+#[command]
+pub fn get_model_pricing() -> Result<std::collections::HashMap<String, ModelPricing>, String> {
+    Ok(load_settings()?.model_pricing)
+}
+
+/// Replace the pricing table. Entries with negative rates are rejected.
+#[command]
+pub fn set_model_pricing(pricing: std::collections::HashMap<String, ModelPricing>) -> Result<AppSettings, String> {
+    if let Some((id, _)) = pricing.iter().find(|(_, p)| p.input_per_mtok < 0.0 || p.output_per_mtok < 0.0) {
+        return Err(format!("Pricing for '{}' must not be negative", id));
+    }
+    let mut settings = load_settings()?;
+    settings.model_pricing = pricing;
+    save_settings(settings.clone())?;
+    Ok(settings)
+}
+
+#[command]
+pub fn get_default_provider_ids() -> Result<std::collections::HashMap<String, String>, String> {
+    Ok(load_settings()?.default_provider_ids)
+}
+
+/// Make `provider_id` the provider used for `provider_type` when several are
+/// configured. An empty id clears the preference.
+#[command]
+pub fn set_default_provider(provider_type: String, provider_id: String) -> Result<AppSettings, String> {
+    let mut settings = load_settings()?;
+    if provider_id.is_empty() {
+        settings.default_provider_ids.remove(&provider_type);
+    } else {
+        let provider = settings
+            .providers
+            .iter()
+            .find(|p| p.id == provider_id)
+            .ok_or_else(|| format!("Provider '{}' not found", provider_id))?;
+        if provider.provider_type != provider_type {
+            return Err(format!(
+                "Provider '{}' is a {} provider, not {}",
+                provider_id, provider.provider_type, provider_type
+            ));
+        }
+        settings.default_provider_ids.insert(provider_type, provider_id);
+    }
+    save_settings(settings.clone())?;
+    Ok(settings)
+}
+
 // ===== Provider Management =====
 
 #[command]
@@ -100,6 +242,7 @@ pub fn update_provider(provider: AiProvider) -> Result<AppSettings, String> {
 pub fn remove_provider(provider_id: String) -> Result<AppSettings, String> {
     let mut settings = load_settings()?;
     settings.providers.retain(|p| p.id != provider_id);
+    settings.default_provider_ids.retain(|_, id| *id != provider_id);
     save_settings(settings.clone())?;
     Ok(settings)
 }
@@ -114,35 +257,317 @@ pub fn derive_api_config(provider_type: &str) -> (&'static str, &'static str) {
         "groq" => ("openai", "https://api.groq.com/openai/v1"),
         "mistral" => ("openai", "https://api.mistral.ai/v1"),
         "google" | "gemini" => ("openai", "https://generativelanguage.googleapis.com/v1beta/openai"),
+        "ollama" => ("openai", "http://localhost:11434/v1"),
         _ => ("openai", ""),
     }
 }
 
+/// Whether a provider has what it needs to make calls. Local Ollama servers
+/// don't need an API key.
+pub fn provider_has_credentials(provider: &AiProvider) -> bool {
+    !provider.api_key.is_empty() || provider.provider_type == "ollama"
+}
+
+/// Timeout for each provider probe in `refresh_provider_health`.
+const HEALTH_CHECK_TIMEOUT_SECS: u32 = 15;
+
 #[command]
 pub fn test_provider(provider: AiProvider) -> Result<String, String> {
-    // Basic field validation
-    if provider.api_key.is_empty() {
+    check_model_exists(&provider)?;
+    probe_provider(&provider, 30)
+}
+
+/// For OpenAI-compatible providers, confirm the configured model is in the
+/// provider's model list before spending a real request on it. Best effort:
+/// if the list can't be fetched the probe goes ahead as usual.
+fn check_model_exists(provider: &AiProvider) -> Result<(), String> {
+    let model = provider.default_model.trim();
+    if model.is_empty() || provider_api_format(provider) == "anthropic" {
+        return Ok(());
+    }
+    let models = match list_provider_models(provider.clone()) {
+        Ok(models) if !models.is_empty() => models,
+        _ => return Ok(()),
+    };
+    if models.iter().any(|m| m.id.eq_ignore_ascii_case(model)) {
+        return Ok(());
+    }
+
+    let wanted = model.to_lowercase();
+    let mut ranked: Vec<(usize, &str)> = models
+        .iter()
+        .map(|m| (edit_distance(&wanted, &m.id.to_lowercase()), m.id.as_str()))
+        .collect();
+    ranked.sort();
+    let closest: Vec<&str> = ranked.iter().take(5).map(|(_, id)| *id).collect();
+    Err(format!(
+        "Model '{}' is not offered by this provider. Closest matches: {}",
+        model,
+        closest.join(", ")
+    ))
+}
+
+/// Levenshtein distance between two strings, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Probe every enabled provider in parallel, record `is_healthy` / `last_error`
+/// and persist the result.
+#[command]
+pub fn refresh_provider_health() -> Result<AppSettings, String> {
+    let mut settings = load_settings()?;
+
+    let results: Vec<(String, Result<String, String>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = settings
+            .providers
+            .iter()
+            .filter(|p| p.enabled)
+            .map(|p| (p.id.clone(), scope.spawn(move || probe_provider(p, HEALTH_CHECK_TIMEOUT_SECS))))
+            .collect();
+        handles
+            .into_iter()
+            .map(|(id, handle)| {
+                let result = handle
+                    .join()
+                    .unwrap_or_else(|_| Err("Health check panicked".to_string()));
+                (id, result)
+            })
+            .collect()
+    });
+
+    for (id, result) in results {
+        if let Some(provider) = settings.providers.iter_mut().find(|p| p.id == id) {
+            provider.is_healthy = result.is_ok();
+            provider.last_error = result.err();
+        }
+    }
+
+    save_settings(settings.clone())?;
+    Ok(settings)
+}
+
+/// The provider's base URL, or the default for its type.
+fn provider_base_url(provider: &AiProvider) -> Result<String, String> {
+    if !provider.api_base_url.is_empty() {
+        return Ok(provider.api_base_url.clone());
+    }
+    let (_, derived_url) = derive_api_config(&provider.provider_type);
+    if derived_url.is_empty() {
+        return Err("API base URL is required for custom providers".to_string());
+    }
+    Ok(derived_url.to_string())
+}
+
+/// Use provider's explicit api_format if set, otherwise derive from provider_type
+fn provider_api_format(provider: &AiProvider) -> String {
+    if !provider.api_format.is_empty() && provider.api_format != "anthropic" && provider.provider_type != "anthropic" && provider.provider_type != "claude" {
+        provider.api_format.clone()
+    } else {
+        derive_api_config(&provider.provider_type).0.to_string()
+    }
+}
+
+/// Models available to the provider's account, from `GET {base}/models` for
+/// OpenAI-compatible APIs. Anthropic providers get the list cached by
+/// `refresh_anthropic_models`, or the presets before the first refresh.
+#[command]
+pub fn list_provider_models(provider: AiProvider) -> Result<Vec<ModelOption>, String> {
+    let presets: Vec<ModelOption> = get_provider_presets()
+        .into_iter()
+        .filter(|p| p.provider_type == provider.provider_type || p.id == provider.provider_type)
+        .flat_map(|p| p.models)
+        .collect();
+
+    if provider_api_format(&provider) == "anthropic" {
+        let cached = load_cached_anthropic_models();
+        if !cached.is_empty() {
+            return Ok(cached);
+        }
+        return Ok(anthropic_preset_models());
+    }
+    if !provider_has_credentials(&provider) {
         return Err("API key is required".to_string());
     }
 
-    let (derived_format, derived_url) = derive_api_config(&provider.provider_type);
+    let url = format!("{}/models", provider_base_url(&provider)?.trim_end_matches('/'));
+    let agent = engine::http::agent_builder(&url)?
+        .timeout(std::time::Duration::from_secs(30))
+        .build();
+    let mut req = agent.get(&url);
+    if !provider.api_key.is_empty() {
+        req = req.set("Authorization", &format!("Bearer {}", provider.api_key));
+    }
+    for (name, value) in &provider.extra_headers {
+        req = req.set(name, value);
+    }
 
-    let api_base_url = if provider.api_base_url.is_empty() {
-        if derived_url.is_empty() {
-            return Err("API base URL is required for custom providers".to_string());
+    let body: serde_json::Value = match req.call() {
+        Ok(resp) => resp.into_json().map_err(|e| format!("Failed to parse model list: {}", e))?,
+        Err(ureq::Error::Status(code, resp)) => {
+            let body = resp.into_string().unwrap_or_default();
+            return Err(format!("Failed to list models (HTTP {}): {}", code, body.chars().take(300).collect::<String>()));
         }
-        derived_url.to_string()
+        Err(e) => return Err(format!("Failed to list models: {}", e)),
+    };
+
+    let mut models: Vec<ModelOption> = body
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|m| m.get("id").and_then(|id| id.as_str()))
+                .map(|id| {
+                    let id = id.trim_start_matches("models/");
+                    presets
+                        .iter()
+                        .find(|p| p.id == id)
+                        .cloned()
+                        .unwrap_or_else(|| ModelOption {
+                            id: id.to_string(),
+                            name: id.to_string(),
+                            tier: guess_model_tier(id).to_string(),
+                            context_window: 0,
+                        })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(models)
+}
+
+fn anthropic_preset_models() -> Vec<ModelOption> {
+    get_provider_presets()
+        .into_iter()
+        .filter(|p| p.provider_type == "anthropic")
+        .flat_map(|p| p.models)
+        .collect()
+}
+
+fn anthropic_models_cache_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("omnihive")
+        .join("anthropic-models.json")
+}
+
+/// Models saved by the last `refresh_anthropic_models`, newest first. Also
+/// hands their ids to the API client for tier resolution.
+pub fn load_cached_anthropic_models() -> Vec<ModelOption> {
+    let models: Vec<ModelOption> = std::fs::read_to_string(anthropic_models_cache_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    engine::api_client::set_anthropic_models(models.iter().map(|m| m.id.clone()).collect());
+    models
+}
+
+/// Fetch the account's models from Anthropic's `GET /v1/models`, cache them
+/// on disk and use them for tier resolution from now on.
+#[command]
+pub fn refresh_anthropic_models(provider: AiProvider) -> Result<Vec<ModelOption>, String> {
+    if provider.api_key.is_empty() {
+        return Err("API key is required".to_string());
+    }
+    let base = if provider.api_base_url.is_empty() {
+        "https://api.anthropic.com".to_string()
     } else {
         provider.api_base_url.clone()
     };
+    let url = format!("{}/v1/models?limit=1000", base.trim_end_matches('/'));
+    let agent = engine::http::agent_builder(&url)?
+        .timeout(std::time::Duration::from_secs(30))
+        .build();
+    let mut req = agent
+        .get(&url)
+        .set("x-api-key", &provider.api_key)
+        .set(
+            "anthropic-version",
+            if provider.anthropic_version.is_empty() { "2023-06-01" } else { &provider.anthropic_version },
+        );
+    for (name, value) in &provider.extra_headers {
+        req = req.set(name, value);
+    }
 
-    // Use provider's explicit api_format if set, otherwise derive from provider_type
-    let api_format = if !provider.api_format.is_empty() && provider.api_format != "anthropic" && provider.provider_type != "anthropic" && provider.provider_type != "claude" {
-        provider.api_format.clone()
-    } else {
-        derived_format.to_string()
+    let body: serde_json::Value = match req.call() {
+        Ok(resp) => resp.into_json().map_err(|e| format!("Failed to parse model list: {}", e))?,
+        Err(ureq::Error::Status(code, resp)) => {
+            let body = resp.into_string().unwrap_or_default();
+            return Err(format!("Failed to list models (HTTP {}): {}", code, body.chars().take(300).collect::<String>()));
+        }
+        Err(e) => return Err(format!("Failed to list models: {}", e)),
     };
 
+    let presets = anthropic_preset_models();
+    // The API lists newest models first; keep that order
+    let models: Vec<ModelOption> = body
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|m| {
+                    let id = m.get("id")?.as_str()?;
+                    let name = m.get("display_name").and_then(|n| n.as_str()).unwrap_or(id);
+                    Some(ModelOption {
+                        id: id.to_string(),
+                        name: name.to_string(),
+                        tier: guess_model_tier(id).to_string(),
+                        context_window: presets.iter().find(|p| p.id == id).map_or(200_000, |p| p.context_window),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    if models.is_empty() {
+        return Err("Anthropic returned no models".to_string());
+    }
+
+    let json = serde_json::to_string_pretty(&models)
+        .map_err(|e| format!("Serialize error: {}", e))?;
+    let cache_path = anthropic_models_cache_path();
+    if let Some(parent) = cache_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    std::fs::write(&cache_path, json)
+        .map_err(|e| format!("Failed to cache models: {}", e))?;
+    engine::api_client::set_anthropic_models(models.iter().map(|m| m.id.clone()).collect());
+    Ok(models)
+}
+
+/// Tier for a model id that isn't in the presets, from common naming patterns.
+fn guess_model_tier(id: &str) -> &'static str {
+    let lower = id.to_lowercase();
+    if ["mini", "haiku", "small", "flash", "lite", "8b", "instant"].iter().any(|k| lower.contains(k)) {
+        "haiku"
+    } else if ["opus", "pro", "large", "reasoner", "o3", "r1"].iter().any(|k| lower.contains(k)) {
+        "opus"
+    } else {
+        "sonnet"
+    }
+}
+
+fn probe_provider(provider: &AiProvider, timeout_secs: u32) -> Result<String, String> {
+    // Basic field validation
+    if !provider_has_credentials(provider) {
+        return Err("API key is required".to_string());
+    }
+
+    let api_base_url = provider_base_url(provider)?;
+    let api_format = provider_api_format(provider);
+
     let model = if provider.default_model.is_empty() {
         match provider.provider_type.as_str() {
             "anthropic" | "claude" => "claude-sonnet-4-20250514".to_string(),
@@ -151,6 +576,8 @@ pub fn test_provider(provider: AiProvider) -> Result<String, String> {
             "deepseek" => "deepseek-chat".to_string(),
             "groq" => "llama-3.1-8b-instant".to_string(),
             "mistral" => "mistral-small-latest".to_string(),
+            "google" | "gemini" => "gemini-2.5-flash".to_string(),
+            "ollama" => "llama3.2".to_string(),
             _ => provider.default_model.clone(),
         }
     } else {
@@ -163,7 +590,7 @@ pub fn test_provider(provider: AiProvider) -> Result<String, String> {
         model,
         system_prompt: "You are a connection test. Respond with exactly: OK".to_string(),
         user_message: "Say OK".to_string(),
-        timeout_secs: 30,
+        timeout_secs,
         anthropic_version: if provider.anthropic_version.is_empty() {
             "2023-06-01".to_string()
         } else {
@@ -172,6 +599,12 @@ pub fn test_provider(provider: AiProvider) -> Result<String, String> {
         extra_headers: provider.extra_headers.clone(),
         force_stream: provider.force_stream,
         api_format,
+        provider_type: provider.provider_type.clone(),
+        on_text_delta: None,
+        enable_prompt_cache: false,
+        history: Vec::new(),
+        include_thinking: provider.include_thinking,
+        extra_body: provider.extra_body.clone(),
     };
 
     match api_client::call_api(&config) {
@@ -179,13 +179,35 @@ pub fn scan_local_skills() -> Result<Vec<ScannedSkill>, String> {
 
 // ===== Custom Skill Management =====
 
-fn get_custom_skills_dir() -> PathBuf {
+pub(crate) fn get_custom_skills_dir() -> PathBuf {
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("omnihive")
         .join("custom-skills")
 }
 
+/// Turn a display name into a directory slug: lowercase ASCII letters,
+/// digits and `-` only.
+pub(crate) fn slugify(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .replace(' ', "-")
+        .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "")
+}
+
+/// Check that a slug is safe to use as a single path component under the
+/// custom skill/agent directories. Only `[a-z0-9-]` is allowed, which also
+/// rules out separators and `..`.
+pub(crate) fn sanitize_slug(slug: &str) -> Result<&str, String> {
+    if slug.is_empty() || slug.contains("..") {
+        return Err(format!("Invalid slug: '{}'", slug));
+    }
+    if !slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid slug: '{}' (only a-z, 0-9 and '-' are allowed)", slug));
+    }
+    Ok(slug)
+}
+
 fn get_custom_agents_dir() -> PathBuf {
     dirs::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -201,22 +223,26 @@ pub struct AddSkillRequest {
     pub category: String,
 }
 
+/// Render the SKILL.md written for custom skills.
+fn render_custom_skill_md(skill: &AddSkillRequest) -> String {
+    format!(
+        "# {}\n\n{}\n\n## Category\n\n{}\n\n## Content\n\n{}",
+        skill.name, skill.description, skill.category, skill.content
+    )
+}
+
 #[command]
 pub fn add_custom_skill(skill: AddSkillRequest) -> Result<SkillInfo, String> {
     let dir = get_custom_skills_dir();
-    let slug = skill.name.to_lowercase()
-        .replace(' ', "-")
-        .replace(|c: char| !c.is_alphanumeric() && c != '-', "");
+    let slug = slugify(&skill.name);
+    sanitize_slug(&slug)?;
 
     let skill_dir = dir.join(&slug);
     fs::create_dir_all(&skill_dir)
         .map_err(|e| format!("Failed to create skill dir: {}", e))?;
 
     // Write SKILL.md
-    let skill_md_content = format!(
-        "# {}\n\n{}\n\n## Category\n\n{}\n\n## Content\n\n{}",
-        skill.name, skill.description, skill.category, skill.content
-    );
+    let skill_md_content = render_custom_skill_md(&skill);
     fs::write(skill_dir.join("SKILL.md"), &skill_md_content)
         .map_err(|e| format!("Failed to write SKILL.md: {}", e))?;
 
@@ -235,7 +261,7 @@ pub fn add_custom_skill(skill: AddSkillRequest) -> Result<SkillInfo, String> {
 
 #[command]
 pub fn remove_custom_skill(skill_id: String) -> Result<bool, String> {
-    let slug = skill_id.strip_prefix("custom:").unwrap_or(&skill_id);
+    let slug = sanitize_slug(skill_id.strip_prefix("custom:").unwrap_or(&skill_id))?;
     let skill_dir = get_custom_skills_dir().join(slug);
 
     if skill_dir.exists() {
@@ -264,11 +290,8 @@ pub fn add_custom_agent(agent: AddAgentRequest) -> Result<PersonaInfo, String> {
     fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create agents dir: {}", e))?;
 
-    let slug = format!(
-        "{}-{}",
-        agent.role.to_lowercase().replace(' ', "-"),
-        agent.name.to_lowercase().replace(' ', "-")
-    ).replace(|c: char| !c.is_alphanumeric() && c != '-', "");
+    let slug = format!("{}-{}", slugify(&agent.role), slugify(&agent.name));
+    sanitize_slug(&slug)?;
 
     let file_path = dir.join(format!("{}.md", slug));
 
@@ -319,7 +342,7 @@ pub fn add_custom_agent(agent: AddAgentRequest) -> Result<PersonaInfo, String> {
 
 #[command]
 pub fn remove_custom_agent(agent_id: String) -> Result<bool, String> {
-    let slug = agent_id.strip_prefix("custom:").unwrap_or(&agent_id);
+    let slug = sanitize_slug(agent_id.strip_prefix("custom:").unwrap_or(&agent_id))?;
     let file_path = get_custom_agents_dir().join(format!("{}.md", slug));
 
     if file_path.exists() {
@@ -460,9 +483,8 @@ fn save_custom_workflows_file(workflows: &[WorkflowInfo]) -> Result<(), String>
 
 #[command]
 pub fn add_custom_workflow(workflow: AddWorkflowRequest) -> Result<WorkflowInfo, String> {
-    let slug = workflow.name.to_lowercase()
-        .replace(' ', "-")
-        .replace(|c: char| !c.is_alphanumeric() && c != '-', "");
+    let slug = slugify(&workflow.name);
+    sanitize_slug(&slug)?;
 
     let new_workflow = WorkflowInfo {
         id: format!("custom:{}", slug),
@@ -507,7 +529,7 @@ pub fn list_custom_workflows() -> Result<Vec<WorkflowInfo>, String> {
 
 #[command]
 pub fn update_custom_agent(agent_id: String, agent: AddAgentRequest) -> Result<PersonaInfo, String> {
-    let slug = agent_id.strip_prefix("custom:").unwrap_or(&agent_id);
+    let slug = sanitize_slug(agent_id.strip_prefix("custom:").unwrap_or(&agent_id))?;
     let dir = get_custom_agents_dir();
     let file_path = dir.join(format!("{}.md", slug));
 
@@ -562,18 +584,21 @@ pub fn update_custom_agent(agent_id: String, agent: AddAgentRequest) -> Result<P
 
 #[command]
 pub fn update_custom_skill(skill_id: String, skill: AddSkillRequest) -> Result<SkillInfo, String> {
-    let slug = skill_id.strip_prefix("custom:").unwrap_or(&skill_id);
+    // The directory name is the skill's identity: it stays the same even when
+    // the new name would produce a different slug.
+    let slug = sanitize_slug(skill_id.strip_prefix("custom:").unwrap_or(&skill_id))?;
+    if skill.name.trim().is_empty() {
+        return Err("Skill name cannot be empty".to_string());
+    }
+
     let dir = get_custom_skills_dir();
     let skill_dir = dir.join(slug);
 
-    if !skill_dir.exists() {
+    if !skill_dir.join("SKILL.md").exists() {
         return Err(format!("Skill not found: {}", skill_id));
     }
 
-    let skill_md_content = format!(
-        "# {}\n\n{}\n\n## Category\n\n{}\n\n## Content\n\n{}",
-        skill.name, skill.description, skill.category, skill.content
-    );
+    let skill_md_content = render_custom_skill_md(&skill);
     fs::write(skill_dir.join("SKILL.md"), &skill_md_content)
         .map_err(|e| format!("Failed to write SKILL.md: {}", e))?;
 
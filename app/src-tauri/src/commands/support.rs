@@ -0,0 +1,132 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use tauri::command;
+use crate::commands::runtime::{get_project_events, resolve_runtime_config};
+use crate::commands::settings::load_settings;
+use crate::commands::system::detect_system;
+use crate::models::*;
+
+/// Number of trailing log lines included in a support bundle.
+const BUNDLE_LOG_LINES: usize = 1000;
+
+/// Patterns for secrets that may appear in logs, config files or serialized state.
+/// Each match keeps its leading key/prefix group and replaces the secret part.
+const SECRET_PATTERNS: &[&str] = &[
+    // key: value / "key": "value" / KEY=value
+    r#"(?i)((?:api[_-]?key|auth[_-]?token|access[_-]?token|secret|password|token)["']?\s*[:=]\s*["']?)[^\s"',}]+"#,
+    // Authorization headers
+    r"(?i)(bearer\s+)[A-Za-z0-9._\-]+",
+    r"(?i)(x-api-key:\s*)\S+",
+    // Well-known key prefixes (Anthropic, OpenAI, OpenRouter, Google, GitHub)
+    r"()\b(?:sk-[A-Za-z0-9_\-]{8,}|AIza[A-Za-z0-9_\-]{20,}|gh[pousr]_[A-Za-z0-9]{20,})",
+    // Query-string keys (e.g. Gemini `?key=...`)
+    r"([?&]key=)[^&\s]+",
+];
+
+/// Strip API keys, tokens and passwords from arbitrary text.
+pub fn redact_secrets(text: &str) -> String {
+    let mut out = text.to_string();
+    for pattern in SECRET_PATTERNS {
+        if let Ok(re) = Regex::new(pattern) {
+            out = re.replace_all(&out, "${1}[REDACTED]").into_owned();
+        }
+    }
+    out
+}
+
+/// Assemble a zip of a project's config, recent logs, history, events and
+/// environment info for attaching to bug reports. Every entry is passed
+/// through `redact_secrets` before it is written.
+#[command]
+pub fn create_support_bundle(project_dir: String, dest_path: String) -> Result<String, String> {
+    let dir = PathBuf::from(&project_dir);
+    if !dir.join("company.yaml").exists() {
+        return Err("Not a valid project directory (missing company.yaml)".to_string());
+    }
+
+    let mut entries: Vec<(String, String)> = Vec::new();
+
+    // Project files, copied as-is (then redacted)
+    for name in [
+        "company.yaml",
+        ".loop.state",
+        ".cycle_history.json",
+        ".runtime_override.json",
+        ".load_balance.json",
+    ] {
+        if let Ok(content) = std::fs::read_to_string(dir.join(name)) {
+            entries.push((name.trim_start_matches('.').to_string(), content));
+        }
+    }
+
+    entries.push(("auto-loop.log".to_string(), tail_file(&dir.join("logs/auto-loop.log"), BUNDLE_LOG_LINES)));
+
+    let events = get_project_events(project_dir.clone(), Some(200))?;
+    entries.push(("events.json".to_string(), to_json(&events)?));
+
+    // Effective guardrails from the project config
+    if let Ok(content) = std::fs::read_to_string(dir.join("company.yaml")) {
+        if let Ok(config) = serde_yaml::from_str::<FactoryConfig>(&content) {
+            entries.push(("guardrails.json".to_string(), to_json(&config.guardrails)?));
+        }
+    }
+
+    // Resolved provider (masked) for the engine/model this project would run with
+    let settings = load_settings()?;
+    let ovr: ProjectRuntimeOverride = std::fs::read_to_string(dir.join(".runtime_override.json"))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default();
+    let provider_info = match resolve_runtime_config(
+        ovr.engine.unwrap_or(settings.default_engine),
+        ovr.model.unwrap_or(settings.default_model),
+    ) {
+        Ok(resolved) => to_json(&resolved)?,
+        Err(e) => format!("{{\"error\": {}}}", serde_json::Value::String(e)),
+    };
+    entries.push(("provider.json".to_string(), provider_info));
+
+    let system = serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "generated_at": chrono::Local::now().format("%+").to_string(),
+        "system": detect_system().ok(),
+    });
+    entries.push(("system.json".to_string(), to_json(&system)?));
+
+    write_bundle(Path::new(&dest_path), &entries)?;
+    Ok(dest_path)
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize bundle entry: {}", e))
+}
+
+fn tail_file(path: &Path, lines: usize) -> String {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    all[start..].join("\n")
+}
+
+fn write_bundle(dest: &Path, entries: &[(String, String)]) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create bundle directory: {}", e))?;
+    }
+    let file = std::fs::File::create(dest)
+        .map_err(|e| format!("Failed to create bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+
+    for (name, content) in entries {
+        zip.start_file(format!("support-bundle/{}", name), options)
+            .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+        zip.write_all(redact_secrets(content).as_bytes())
+            .map_err(|e| format!("Failed to write {} to bundle: {}", name, e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finalize bundle: {}", e))?;
+    Ok(())
+}
@@ -28,6 +28,67 @@ pub fn detect_system() -> Result<SystemInfo, String> {
 
 #[command]
 pub fn install_tool(tool_name: String, install_dir: Option<String>) -> Result<String, String> {
+    let package = npm_package(&tool_name)?;
+
+    let mut args = vec!["install", "-g", package];
+
+    // If a custom prefix is specified, use it
+    let prefix_flag;
+    if let Some(ref dir) = install_dir {
+        prefix_flag = format!("--prefix={}", dir);
+        args.push(&prefix_flag);
+    }
+
+    let o = run_npm(&args)?;
+    let stdout = String::from_utf8_lossy(&o.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&o.stderr).to_string();
+    if o.status.success() {
+        Ok(format!("Successfully installed {}.\n{}", package, stdout))
+    } else {
+        Err(format!(
+            "Installation failed (exit {}):\n{}\n{}",
+            o.status, stdout, stderr
+        ))
+    }
+}
+
+#[command]
+pub fn uninstall_tool(tool_name: String) -> Result<String, String> {
+    let package = npm_package(&tool_name)?;
+
+    let o = run_npm(&["uninstall", "-g", package])?;
+    let stdout = String::from_utf8_lossy(&o.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&o.stderr).to_string();
+    if o.status.success() {
+        Ok(format!("Successfully uninstalled {}.\n{}", package, stdout))
+    } else {
+        Err(format!(
+            "Uninstall failed (exit {}):\n{}\n{}",
+            o.status, stdout, stderr
+        ))
+    }
+}
+
+#[command]
+pub fn update_tool(tool_name: String) -> Result<String, String> {
+    let package = npm_package(&tool_name)?;
+    let latest = format!("{}@latest", package);
+
+    let o = run_npm(&["install", "-g", &latest])?;
+    let stdout = String::from_utf8_lossy(&o.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&o.stderr).to_string();
+    if o.status.success() {
+        Ok(format!("Successfully updated {}.\n{}", package, stdout))
+    } else {
+        Err(format!(
+            "Update failed (exit {}):\n{}\n{}",
+            o.status, stdout, stderr
+        ))
+    }
+}
+
+/// Map a tool name to its global npm package.
+fn npm_package(tool_name: &str) -> Result<&'static str, String> {
     // Verify npm is available
     if find_binary("npm").is_none() {
         return Err(
@@ -35,7 +96,7 @@ pub fn install_tool(tool_name: String, install_dir: Option<String>) -> Result<St
         );
     }
 
-    let (package, needs_npm) = match tool_name.as_str() {
+    let (package, needs_npm) = match tool_name {
         "claude" => ("@anthropic-ai/claude-code", true),
         "codex" => ("@openai/codex", true),
         "opencode" => ("opencode", false),
@@ -44,20 +105,16 @@ pub fn install_tool(tool_name: String, install_dir: Option<String>) -> Result<St
 
     if !needs_npm {
         return Err(format!(
-            "{} cannot be installed via npm. Please install it manually.",
+            "{} cannot be managed via npm. Please install it manually.",
             tool_name
         ));
     }
 
-    let mut args = vec!["install", "-g", package];
-
-    // If a custom prefix is specified, use it
-    let prefix_flag;
-    if let Some(ref dir) = install_dir {
-        prefix_flag = format!("--prefix={}", dir);
-        args.push(&prefix_flag);
-    }
+    Ok(package)
+}
 
+/// Run npm with `args`, going through `cmd /C` for `.cmd` shims on Windows.
+fn run_npm(args: &[&str]) -> Result<std::process::Output, String> {
     let npm_path = find_binary("npm")
         .ok_or_else(|| "npm not found in PATH".to_string())?;
 
@@ -67,35 +124,20 @@ pub fn install_tool(tool_name: String, install_dir: Option<String>) -> Result<St
         silent_command("cmd")
             .arg("/C")
             .arg(&npm_path)
-            .args(&args)
+            .args(args)
             .output()
     } else {
         silent_command(&npm_path)
-            .args(&args)
+            .args(args)
             .output()
     };
 
     #[cfg(not(target_os = "windows"))]
     let output = silent_command(&npm_path)
-        .args(&args)
+        .args(args)
         .output();
 
-    match output {
-        Ok(o) => {
-            let stdout = String::from_utf8_lossy(&o.stdout).to_string();
-            let stderr = String::from_utf8_lossy(&o.stderr).to_string();
-
-            if o.status.success() {
-                Ok(format!("Successfully installed {}.\n{}", package, stdout))
-            } else {
-                Err(format!(
-                    "Installation failed (exit {}):\n{}\n{}",
-                    o.status, stdout, stderr
-                ))
-            }
-        }
-        Err(e) => Err(format!("Failed to run npm: {}", e)),
-    }
+    output.map_err(|e| format!("Failed to run npm: {}", e))
 }
 
 #[command]
@@ -204,6 +246,47 @@ fn detect_tools() -> Vec<ToolInfo> {
             "go install github.com/opencode-ai/opencode@latest",
             "https://github.com/opencode-ai/opencode",
         ),
+        // Prerequisites for skills and MCP servers
+        detect_tool_info(
+            "python",
+            "Python",
+            &["python3", "python", "py"],
+            &["--version"],
+            "Install Python 3 from python.org",
+            "https://www.python.org/downloads/",
+        ),
+        detect_tool_info(
+            "uv",
+            "uv",
+            &["uv"],
+            &["--version"],
+            "pip install uv",
+            "https://docs.astral.sh/uv/getting-started/installation/",
+        ),
+        detect_tool_info(
+            "npx",
+            "npx",
+            &["npx"],
+            &["--version"],
+            "Install Node.js (includes npx)",
+            "https://nodejs.org/",
+        ),
+        detect_tool_info(
+            "git",
+            "Git",
+            &["git"],
+            &["--version"],
+            "Install Git from git-scm.com",
+            "https://git-scm.com/downloads",
+        ),
+        detect_tool_info(
+            "docker",
+            "Docker",
+            &["docker"],
+            &["--version"],
+            "Install Docker Desktop",
+            "https://docs.docker.com/get-docker/",
+        ),
     ]
 }
 
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::BufRead;
+use std::sync::RwLock;
 use std::time::Duration;
 
 // ===== Configurable API Call =====
 
+/// Callback receiving incremental text from streaming responses.
+pub type TextDeltaCallback = Box<dyn Fn(&str) + Send>;
+
 pub struct ApiCallConfig {
     pub api_key: String,
     pub api_base_url: String,
@@ -15,7 +19,21 @@ pub struct ApiCallConfig {
     pub anthropic_version: String,
     pub extra_headers: HashMap<String, String>,
     pub force_stream: bool,
-    pub api_format: String, // "anthropic" | "claude-code" | "openai"
+    pub api_format: String, // "anthropic" | "claude-code" | "openai" | "gemini"
+    /// Provider type from settings (e.g. "openrouter"), used for
+    /// provider-specific request tweaks.
+    pub provider_type: String,
+    /// Called with each text delta as it arrives on streaming responses.
+    pub on_text_delta: Option<TextDeltaCallback>,
+    /// Mark the system prompt as an Anthropic prompt-cache breakpoint.
+    pub enable_prompt_cache: bool,
+    /// Earlier user/assistant turns sent ahead of `user_message`.
+    pub history: Vec<ApiMessage>,
+    /// Prepend Anthropic `thinking` blocks (wrapped in <thinking> tags) to the text.
+    pub include_thinking: bool,
+    /// Top-level fields merged into the request JSON (OpenAI and Anthropic
+    /// formats), overriding any field of the same name.
+    pub extra_body: HashMap<String, serde_json::Value>,
 }
 
 impl Default for ApiCallConfig {
@@ -31,6 +49,12 @@ impl Default for ApiCallConfig {
             extra_headers: HashMap::new(),
             force_stream: false,
             api_format: "anthropic".to_string(),
+            provider_type: String::new(),
+            on_text_delta: None,
+            enable_prompt_cache: false,
+            history: Vec::new(),
+            include_thinking: false,
+            extra_body: HashMap::new(),
         }
     }
 }
@@ -47,22 +71,27 @@ struct AnthropicRequest {
     stream: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiMessage {
-    role: String,
-    content: String,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiMessage {
+    pub role: String, // "user" | "assistant" (| "system" for OpenAI)
+    pub content: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct AnthropicResponse {
     content: Vec<ContentBlock>,
     usage: AnthropicUsage,
+    #[serde(default)]
+    stop_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ContentBlock {
     #[serde(default)]
     text: Option<String>,
+    /// Set on `thinking` blocks
+    #[serde(default)]
+    thinking: Option<String>,
     #[serde(rename = "type")]
     content_type: String,
 }
@@ -71,6 +100,17 @@ struct ContentBlock {
 struct AnthropicUsage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+}
+
+impl AnthropicUsage {
+    /// Input tokens including those written to or read from the prompt cache.
+    fn total_input(&self) -> u32 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
 }
 
 // ===== OpenAI API Types =====
@@ -78,8 +118,16 @@ struct AnthropicUsage {
 #[derive(Debug, Serialize)]
 struct OpenAiRequest {
     model: String,
-    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    /// Reasoning models (o1/o3/o4) reject `max_tokens` and require this instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
     messages: Vec<ApiMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,6 +139,8 @@ struct OpenAiResponse {
 #[derive(Debug, Deserialize)]
 struct OpenAiChoice {
     message: OpenAiMessage,
+    #[serde(default)]
+    finish_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -104,6 +154,80 @@ struct OpenAiUsage {
     completion_tokens: u32,
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChunk {
+    #[serde(default)]
+    choices: Vec<OpenAiStreamChoice>,
+    /// Only present on the final chunk when `include_usage` is requested
+    #[serde(default)]
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiStreamChoice {
+    #[serde(default)]
+    delta: Option<OpenAiMessage>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+// ===== Gemini API Types =====
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    system_instruction: GeminiContent,
+    generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    role: Option<String>,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiPart {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    max_output_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: Option<GeminiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    #[serde(default)]
+    content: Option<GeminiContent>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+}
+
 // ===== SSE Streaming Types =====
 
 #[derive(Debug, Deserialize)]
@@ -122,6 +246,9 @@ struct StreamDelta {
     delta_type: String,
     #[serde(default)]
     text: Option<String>,
+    /// Set on `message_delta` events
+    #[serde(default)]
+    stop_reason: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -136,6 +263,109 @@ pub struct CycleResponse {
     pub text: String,
     pub input_tokens: u32,
     pub output_tokens: u32,
+    /// Why generation ended, normalized so truncation is always "max_tokens"
+    pub stop_reason: Option<String>,
+}
+
+impl CycleResponse {
+    /// The model ran out of output tokens before finishing.
+    pub fn truncated(&self) -> bool {
+        self.stop_reason.as_deref() == Some("max_tokens")
+    }
+}
+
+/// Map each API's finish reason onto Anthropic's names where they overlap
+/// (OpenAI "length" and Gemini "MAX_TOKENS" both become "max_tokens").
+fn normalize_stop_reason(raw: &str) -> String {
+    match raw {
+        "length" | "MAX_TOKENS" => "max_tokens".to_string(),
+        other => other.to_lowercase(),
+    }
+}
+
+/// Broad category of a failed API call, derived from the status code and
+/// body embedded in the error strings returned by `call_api`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    AuthError,
+    RateLimited,
+    QuotaExceeded,
+    ModelNotFound,
+    ServerError,
+    Network,
+    Other,
+}
+
+impl ApiErrorKind {
+    pub fn classify(error: &str) -> Self {
+        let lower = error.to_lowercase();
+        let status = error
+            .split("(HTTP ")
+            .nth(1)
+            .and_then(|rest| rest.split(')').next())
+            .and_then(|code| code.trim().parse::<u16>().ok());
+
+        let Some(status) = status else {
+            return if lower.contains("request failed") || lower.contains("stream read error") {
+                ApiErrorKind::Network
+            } else {
+                ApiErrorKind::Other
+            };
+        };
+
+        // Several providers report exhausted credit as 429, so check the body first
+        let quota = ["insufficient_quota", "quota", "billing", "credit balance", "insufficient balance"]
+            .iter()
+            .any(|k| lower.contains(k));
+        let model_missing = lower.contains("model")
+            && ["not found", "not_found", "does not exist", "invalid model", "unknown model"]
+                .iter()
+                .any(|k| lower.contains(k));
+
+        match status {
+            401 | 403 => ApiErrorKind::AuthError,
+            402 => ApiErrorKind::QuotaExceeded,
+            429 if quota => ApiErrorKind::QuotaExceeded,
+            429 => ApiErrorKind::RateLimited,
+            404 => ApiErrorKind::ModelNotFound,
+            400 if model_missing => ApiErrorKind::ModelNotFound,
+            s if s >= 500 => ApiErrorKind::ServerError,
+            _ => ApiErrorKind::Other,
+        }
+    }
+
+    /// Errors that retrying with the same settings cannot fix.
+    pub fn is_fatal(self) -> bool {
+        matches!(
+            self,
+            ApiErrorKind::AuthError | ApiErrorKind::QuotaExceeded | ApiErrorKind::ModelNotFound
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ApiErrorKind::AuthError => "auth_error",
+            ApiErrorKind::RateLimited => "rate_limited",
+            ApiErrorKind::QuotaExceeded => "quota_exceeded",
+            ApiErrorKind::ModelNotFound => "model_not_found",
+            ApiErrorKind::ServerError => "server_error",
+            ApiErrorKind::Network => "network",
+            ApiErrorKind::Other => "other",
+        }
+    }
+
+    /// What the user should do about it.
+    pub fn hint(self) -> &'static str {
+        match self {
+            ApiErrorKind::AuthError => "Authentication failed - check your API key in Settings.",
+            ApiErrorKind::RateLimited => "Rate limited by the provider - backing off before the next cycle.",
+            ApiErrorKind::QuotaExceeded => "Quota or credit exhausted - top up the account or switch providers.",
+            ApiErrorKind::ModelNotFound => "Model not found - check the model name for this provider.",
+            ApiErrorKind::ServerError => "Provider server error - will retry.",
+            ApiErrorKind::Network => "Network error - check your connection or proxy settings.",
+            ApiErrorKind::Other => "API call failed.",
+        }
+    }
 }
 
 // ===== Unified API Call =====
@@ -143,14 +373,9 @@ pub struct CycleResponse {
 pub fn call_api(config: &ApiCallConfig) -> Result<CycleResponse, String> {
     let format = config.api_format.as_str();
     match format {
-        "openai" => call_openai(
-            &config.api_key,
-            &config.api_base_url,
-            &config.model,
-            &config.system_prompt,
-            &config.user_message,
-            config.timeout_secs,
-        ),
+        "openai" if config.force_stream => call_openai_streaming(config),
+        "openai" => call_openai_configurable(config),
+        "gemini" => call_gemini(config),
         "anthropic" | "claude-code" | _ => {
             if config.force_stream {
                 call_anthropic_streaming(config)
@@ -167,20 +392,17 @@ fn call_anthropic_configurable(config: &ApiCallConfig) -> Result<CycleResponse,
     let url = format!("{}/v1/messages", config.api_base_url.trim_end_matches('/'));
     let resolved_model = resolve_anthropic_model(&config.model);
 
-    let system_value = build_system_value(&config.system_prompt, &config.api_format);
+    let system_value = build_system_value(&config.system_prompt, &config.api_format, config.enable_prompt_cache);
 
     let body = AnthropicRequest {
         model: resolved_model,
         max_tokens: 4096,
         system: system_value,
-        messages: vec![ApiMessage {
-            role: "user".to_string(),
-            content: config.user_message.clone(),
-        }],
+        messages: conversation_messages(config),
         stream: None,
     };
 
-    let agent = ureq::AgentBuilder::new()
+    let agent = super::http::agent_builder(&url)?
         .timeout_read(Duration::from_secs(config.timeout_secs as u64))
         .timeout_write(Duration::from_secs(30))
         .build();
@@ -191,12 +413,16 @@ fn call_anthropic_configurable(config: &ApiCallConfig) -> Result<CycleResponse,
         .set("anthropic-version", &config.anthropic_version)
         .set("content-type", "application/json");
 
+    if config.enable_prompt_cache {
+        req = req.set("anthropic-beta", PROMPT_CACHE_BETA);
+    }
+
     // Apply extra headers
     for (key, value) in &config.extra_headers {
         req = req.set(key, value);
     }
 
-    let result = req.send_json(&body);
+    let result = req.send_json(with_extra_body(&body, &config.extra_body)?);
 
     match result {
         Ok(resp) => {
@@ -204,23 +430,32 @@ fn call_anthropic_configurable(config: &ApiCallConfig) -> Result<CycleResponse,
                 .into_json()
                 .map_err(|e| format!("Failed to parse Anthropic response: {}", e))?;
 
-            let text = data
-                .content
-                .into_iter()
-                .filter_map(|c| {
-                    if c.content_type == "text" {
-                        c.text
-                    } else {
-                        None
+            // Reasoning models may return thinking/tool_use blocks; an answer
+            // with no text block at all is an error, not an empty cycle.
+            if !data.content.is_empty() && !data.content.iter().any(|c| c.content_type == "text") {
+                let kinds: Vec<&str> = data.content.iter().map(|c| c.content_type.as_str()).collect();
+                return Err(format!(
+                    "Anthropic response contained no text blocks (got: {}); the model may need a higher max_tokens",
+                    kinds.join(", ")
+                ));
+            }
+
+            let mut text = String::new();
+            for block in data.content {
+                match (block.content_type.as_str(), block.text, block.thinking) {
+                    ("text", Some(t), _) => text.push_str(&t),
+                    ("thinking", _, Some(t)) if config.include_thinking => {
+                        text.push_str(&format!("<thinking>\n{}\n</thinking>\n\n", t.trim()));
                     }
-                })
-                .collect::<Vec<_>>()
-                .join("");
+                    _ => {}
+                }
+            }
 
             Ok(CycleResponse {
                 text,
-                input_tokens: data.usage.input_tokens,
+                input_tokens: data.usage.total_input(),
                 output_tokens: data.usage.output_tokens,
+                stop_reason: data.stop_reason.as_deref().map(normalize_stop_reason),
             })
         }
         Err(ureq::Error::Status(code, resp)) => {
@@ -238,20 +473,17 @@ fn call_anthropic_streaming(config: &ApiCallConfig) -> Result<CycleResponse, Str
     let url = format!("{}/v1/messages", config.api_base_url.trim_end_matches('/'));
     let resolved_model = resolve_anthropic_model(&config.model);
 
-    let system_value = build_system_value(&config.system_prompt, &config.api_format);
+    let system_value = build_system_value(&config.system_prompt, &config.api_format, config.enable_prompt_cache);
 
     let body = AnthropicRequest {
         model: resolved_model,
         max_tokens: 4096,
         system: system_value,
-        messages: vec![ApiMessage {
-            role: "user".to_string(),
-            content: config.user_message.clone(),
-        }],
+        messages: conversation_messages(config),
         stream: Some(true),
     };
 
-    let agent = ureq::AgentBuilder::new()
+    let agent = super::http::agent_builder(&url)?
         .timeout_read(Duration::from_secs(config.timeout_secs as u64))
         .timeout_write(Duration::from_secs(30))
         .build();
@@ -262,14 +494,18 @@ fn call_anthropic_streaming(config: &ApiCallConfig) -> Result<CycleResponse, Str
         .set("anthropic-version", &config.anthropic_version)
         .set("content-type", "application/json");
 
+    if config.enable_prompt_cache {
+        req = req.set("anthropic-beta", PROMPT_CACHE_BETA);
+    }
+
     for (key, value) in &config.extra_headers {
         req = req.set(key, value);
     }
 
-    let result = req.send_json(&body);
+    let result = req.send_json(with_extra_body(&body, &config.extra_body)?);
 
     match result {
-        Ok(resp) => parse_sse_stream(resp),
+        Ok(resp) => parse_sse_stream(resp, config.on_text_delta.as_deref()),
         Err(ureq::Error::Status(code, resp)) => {
             let error_body = resp.into_string().unwrap_or_default();
             let preview = truncate(&error_body, 2000);
@@ -279,11 +515,12 @@ fn call_anthropic_streaming(config: &ApiCallConfig) -> Result<CycleResponse, Str
     }
 }
 
-fn parse_sse_stream(resp: ureq::Response) -> Result<CycleResponse, String> {
+fn parse_sse_stream(resp: ureq::Response, on_text_delta: Option<&(dyn Fn(&str) + Send)>) -> Result<CycleResponse, String> {
     let reader = std::io::BufReader::new(resp.into_reader());
     let mut full_text = String::new();
     let mut input_tokens: u32 = 0;
     let mut output_tokens: u32 = 0;
+    let mut stop_reason: Option<String> = None;
 
     for line_result in reader.lines() {
         let line = line_result.map_err(|e| format!("Stream read error: {}", e))?;
@@ -301,18 +538,23 @@ fn parse_sse_stream(resp: ureq::Response) -> Result<CycleResponse, String> {
                             if delta.delta_type == "text_delta" {
                                 if let Some(ref text) = delta.text {
                                     full_text.push_str(text);
+                                    if let Some(callback) = on_text_delta {
+                                        callback(text);
+                                    }
                                 }
                             }
                         }
                     }
                     "message_delta" => {
-                        // message_delta may contain final usage in delta
+                        if let Some(reason) = event.delta.as_ref().and_then(|d| d.stop_reason.as_deref()) {
+                            stop_reason = Some(normalize_stop_reason(reason));
+                        }
                     }
                     "message_start" => {
                         // message_start may contain usage.input_tokens
                         if let Some(msg) = &event.message {
                             if let Some(usage) = &msg.usage {
-                                input_tokens = usage.input_tokens;
+                                input_tokens = usage.total_input();
                                 output_tokens = usage.output_tokens;
                             }
                         }
@@ -335,6 +577,7 @@ fn parse_sse_stream(resp: ureq::Response) -> Result<CycleResponse, String> {
         text: full_text,
         input_tokens,
         output_tokens,
+        stop_reason,
     })
 }
 
@@ -362,6 +605,15 @@ pub fn call_anthropic(
 
 // ===== OpenAI API =====
 
+/// OpenAI o-series reasoning models (`o1`, `o3`, `o4-mini`, ...), optionally
+/// behind a vendor prefix such as `openai/o3` on OpenRouter.
+fn is_openai_reasoning_model(model: &str) -> bool {
+    let id = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    ["o1", "o3", "o4"].iter().any(|prefix| {
+        id == *prefix || id.starts_with(&format!("{}-", prefix))
+    })
+}
+
 pub fn call_openai(
     api_key: &str,
     api_base_url: &str,
@@ -369,54 +621,80 @@ pub fn call_openai(
     system_prompt: &str,
     user_message: &str,
     timeout_secs: u32,
+) -> Result<CycleResponse, String> {
+    let messages = vec![ApiMessage {
+        role: "user".to_string(),
+        content: user_message.to_string(),
+    }];
+    call_openai_messages(api_key, api_base_url, model, system_prompt, messages, timeout_secs)
+}
+
+/// Like `call_openai`, but with a full user/assistant message list; the
+/// system prompt is prepended as the first message.
+pub fn call_openai_messages(
+    api_key: &str,
+    api_base_url: &str,
+    model: &str,
+    system_prompt: &str,
+    messages: Vec<ApiMessage>,
+    timeout_secs: u32,
 ) -> Result<CycleResponse, String> {
     let url = format!(
         "{}/v1/chat/completions",
         api_base_url.trim_end_matches('/')
     );
+    let body = serde_json::to_value(openai_request(model, system_prompt, messages))
+        .map_err(|e| format!("Failed to serialize request: {}", e))?;
+    send_openai(&url, api_key, timeout_secs, &body, &HashMap::new())
+}
 
-    let body = OpenAiRequest {
-        model: model.to_string(),
-        max_tokens: 4096,
-        messages: vec![
-            ApiMessage {
-                role: "system".to_string(),
-                content: system_prompt.to_string(),
-            },
-            ApiMessage {
-                role: "user".to_string(),
-                content: user_message.to_string(),
-            },
-        ],
-    };
+fn call_openai_configurable(config: &ApiCallConfig) -> Result<CycleResponse, String> {
+    let url = format!(
+        "{}/v1/chat/completions",
+        config.api_base_url.trim_end_matches('/')
+    );
+    let body = with_extra_body(
+        &openai_request(&config.model, &config.system_prompt, conversation_messages(config)),
+        &config.extra_body,
+    )?;
+    send_openai(&url, &config.api_key, config.timeout_secs, &body, &request_headers(config))
+}
 
-    let agent = ureq::AgentBuilder::new()
+fn send_openai(
+    url: &str,
+    api_key: &str,
+    timeout_secs: u32,
+    body: &serde_json::Value,
+    extra_headers: &HashMap<String, String>,
+) -> Result<CycleResponse, String> {
+    let agent = super::http::agent_builder(url)?
         .timeout_read(Duration::from_secs(timeout_secs as u64))
         .timeout_write(Duration::from_secs(30))
         .build();
 
-    let result = agent
-        .post(&url)
+    let mut req = agent
+        .post(url)
         .set("Authorization", &format!("Bearer {}", api_key))
-        .set("content-type", "application/json")
-        .send_json(&body);
+        .set("content-type", "application/json");
 
-    match result {
+    for (key, value) in extra_headers {
+        req = req.set(key, value);
+    }
+
+    match req.send_json(body) {
         Ok(resp) => {
             let data: OpenAiResponse = resp
                 .into_json()
                 .map_err(|e| format!("Failed to parse OpenAI response: {}", e))?;
 
-            let text = data
-                .choices
-                .first()
-                .and_then(|c| c.message.content.clone())
-                .unwrap_or_default();
+            let choice = data.choices.first();
+            let text = choice.and_then(|c| c.message.content.clone()).unwrap_or_default();
 
             Ok(CycleResponse {
                 text,
                 input_tokens: data.usage.prompt_tokens,
                 output_tokens: data.usage.completion_tokens,
+                stop_reason: choice.and_then(|c| c.finish_reason.as_deref()).map(normalize_stop_reason),
             })
         }
         Err(ureq::Error::Status(code, resp)) => {
@@ -428,9 +706,255 @@ pub fn call_openai(
     }
 }
 
+/// Serialize a request body and merge the provider's `extra_body` fields
+/// into its top level.
+fn with_extra_body<T: Serialize>(body: &T, extra: &HashMap<String, serde_json::Value>) -> Result<serde_json::Value, String> {
+    let mut value = serde_json::to_value(body).map_err(|e| format!("Failed to serialize request: {}", e))?;
+    if let Some(fields) = value.as_object_mut() {
+        for (key, extra_value) in extra {
+            fields.insert(key.clone(), extra_value.clone());
+        }
+    }
+    Ok(value)
+}
+
+/// Default attribution headers OpenRouter asks clients to send.
+const OPENROUTER_HEADERS: &[(&str, &str)] = &[
+    ("HTTP-Referer", "https://github.com/KuaaMU/omnihive"),
+    ("X-Title", "Omnihive"),
+];
+
+/// The user's extra headers plus any provider-specific defaults they have
+/// not overridden (header names compare case-insensitively).
+fn request_headers(config: &ApiCallConfig) -> HashMap<String, String> {
+    let mut headers = config.extra_headers.clone();
+    if config.provider_type == "openrouter" {
+        for (name, value) in OPENROUTER_HEADERS {
+            if !headers.keys().any(|k| k.eq_ignore_ascii_case(name)) {
+                headers.insert(name.to_string(), value.to_string());
+            }
+        }
+    }
+    headers
+}
+
+fn openai_request(model: &str, system_prompt: &str, messages: Vec<ApiMessage>) -> OpenAiRequest {
+    let reasoning = is_openai_reasoning_model(model);
+    OpenAiRequest {
+        model: model.to_string(),
+        max_tokens: if reasoning { None } else { Some(4096) },
+        max_completion_tokens: if reasoning { Some(16384) } else { None },
+        messages: std::iter::once(ApiMessage {
+            role: "system".to_string(),
+            content: system_prompt.to_string(),
+        })
+        .chain(messages)
+        .collect(),
+        stream: None,
+        stream_options: None,
+    }
+}
+
+// ===== OpenAI Streaming API =====
+
+fn call_openai_streaming(config: &ApiCallConfig) -> Result<CycleResponse, String> {
+    let url = format!(
+        "{}/v1/chat/completions",
+        config.api_base_url.trim_end_matches('/')
+    );
+
+    let mut body = openai_request(&config.model, &config.system_prompt, conversation_messages(config));
+    body.stream = Some(true);
+    body.stream_options = Some(serde_json::json!({"include_usage": true}));
+
+    let agent = super::http::agent_builder(&url)?
+        .timeout_read(Duration::from_secs(config.timeout_secs as u64))
+        .timeout_write(Duration::from_secs(30))
+        .build();
+
+    let mut req = agent
+        .post(&url)
+        .set("Authorization", &format!("Bearer {}", config.api_key))
+        .set("content-type", "application/json");
+
+    for (key, value) in &request_headers(config) {
+        req = req.set(key, value);
+    }
+
+    match req.send_json(with_extra_body(&body, &config.extra_body)?) {
+        Ok(resp) => parse_openai_sse_stream(resp, config.on_text_delta.as_deref()),
+        Err(ureq::Error::Status(code, resp)) => {
+            let error_body = resp.into_string().unwrap_or_default();
+            let preview = truncate(&error_body, 2000);
+            Err(format!("OpenAI Streaming API error (HTTP {}): {}", code, preview))
+        }
+        Err(e) => Err(format!("OpenAI streaming request failed: {}", e)),
+    }
+}
+
+fn parse_openai_sse_stream(resp: ureq::Response, on_text_delta: Option<&(dyn Fn(&str) + Send)>) -> Result<CycleResponse, String> {
+    let reader = std::io::BufReader::new(resp.into_reader());
+    let mut full_text = String::new();
+    let mut input_tokens: u32 = 0;
+    let mut output_tokens: u32 = 0;
+    let mut stop_reason: Option<String> = None;
+
+    for line_result in reader.lines() {
+        let line = line_result.map_err(|e| format!("Stream read error: {}", e))?;
+
+        let Some(data) = line.strip_prefix("data:").map(|d| d.trim()) else {
+            continue;
+        };
+        if data == "[DONE]" {
+            break;
+        }
+
+        if let Ok(chunk) = serde_json::from_str::<OpenAiStreamChunk>(data) {
+            for choice in chunk.choices {
+                if let Some(reason) = choice.finish_reason.as_deref() {
+                    stop_reason = Some(normalize_stop_reason(reason));
+                }
+                if let Some(text) = choice.delta.and_then(|d| d.content) {
+                    full_text.push_str(&text);
+                    if let Some(callback) = on_text_delta {
+                        callback(&text);
+                    }
+                }
+            }
+            if let Some(usage) = chunk.usage {
+                input_tokens = usage.prompt_tokens;
+                output_tokens = usage.completion_tokens;
+            }
+        }
+    }
+
+    // Estimate output tokens from text length if the provider sent no usage
+    if output_tokens == 0 && !full_text.is_empty() {
+        output_tokens = (full_text.len() as u32) / 4;
+    }
+
+    Ok(CycleResponse {
+        text: full_text,
+        input_tokens,
+        output_tokens,
+        stop_reason,
+    })
+}
+
+// ===== Gemini API (native generateContent) =====
+
+fn call_gemini(config: &ApiCallConfig) -> Result<CycleResponse, String> {
+    // Providers default to the OpenAI-compatible shim URL; the native API lives one level up
+    let base = config
+        .api_base_url
+        .trim_end_matches('/')
+        .trim_end_matches("/openai");
+    let model = config.model.trim_start_matches("models/");
+    let url = format!("{}/models/{}:generateContent?key={}", base, model, config.api_key);
+
+    let body = GeminiRequest {
+        contents: conversation_messages(config)
+            .into_iter()
+            .map(|m| GeminiContent {
+                // Gemini calls the assistant side "model"
+                role: Some(if m.role == "assistant" { "model".to_string() } else { m.role }),
+                parts: vec![GeminiPart { text: Some(m.content) }],
+            })
+            .collect(),
+        system_instruction: GeminiContent {
+            role: None,
+            parts: vec![GeminiPart {
+                text: Some(config.system_prompt.clone()),
+            }],
+        },
+        generation_config: GeminiGenerationConfig {
+            max_output_tokens: 4096,
+        },
+    };
+
+    let agent = super::http::agent_builder(&url)?
+        .timeout_read(Duration::from_secs(config.timeout_secs as u64))
+        .timeout_write(Duration::from_secs(30))
+        .build();
+
+    let mut req = agent.post(&url).set("content-type", "application/json");
+
+    for (key, value) in &config.extra_headers {
+        req = req.set(key, value);
+    }
+
+    let result = req.send_json(&body);
+
+    match result {
+        Ok(resp) => {
+            let data: GeminiResponse = resp
+                .into_json()
+                .map_err(|e| format!("Failed to parse Gemini response: {}", e))?;
+
+            let candidate = data.candidates.into_iter().next();
+            let stop_reason = candidate
+                .as_ref()
+                .and_then(|c| c.finish_reason.as_deref())
+                .map(normalize_stop_reason);
+            let text = candidate
+                .and_then(|c| c.content)
+                .map(|content| {
+                    content
+                        .parts
+                        .into_iter()
+                        .filter_map(|p| p.text)
+                        .collect::<Vec<_>>()
+                        .join("")
+                })
+                .unwrap_or_default();
+
+            let (input_tokens, output_tokens) = data
+                .usage_metadata
+                .map(|u| (u.prompt_token_count, u.candidates_token_count))
+                .unwrap_or((0, 0));
+
+            Ok(CycleResponse {
+                text,
+                input_tokens,
+                output_tokens,
+                stop_reason,
+            })
+        }
+        Err(ureq::Error::Status(code, resp)) => {
+            let error_body = resp.into_string().unwrap_or_default();
+            let preview = truncate(&error_body, 2000);
+            Err(format!("Gemini API error (HTTP {}): {}", code, preview))
+        }
+        // Don't echo the URL here: it carries the API key as a query parameter
+        Err(ureq::Error::Transport(e)) => Err(format!("Gemini request failed: {}", e.kind())),
+    }
+}
+
+// ===== Message Builder =====
+
+/// The conversation history followed by the current user message.
+fn conversation_messages(config: &ApiCallConfig) -> Vec<ApiMessage> {
+    let mut messages = config.history.clone();
+    messages.push(ApiMessage {
+        role: "user".to_string(),
+        content: config.user_message.clone(),
+    });
+    messages
+}
+
 // ===== System Value Builder =====
 
-fn build_system_value(system_prompt: &str, api_format: &str) -> serde_json::Value {
+const PROMPT_CACHE_BETA: &str = "prompt-caching-2024-07-31";
+
+fn build_system_value(system_prompt: &str, api_format: &str, cache: bool) -> serde_json::Value {
+    if cache {
+        // Cache breakpoint on the (largely static) system prompt
+        return serde_json::json!([{
+            "type": "text",
+            "text": system_prompt,
+            "cache_control": {"type": "ephemeral"}
+        }]);
+    }
     match api_format {
         "claude-code" => {
             // Claude Code compatible: system as array of content blocks
@@ -445,11 +969,28 @@ fn build_system_value(system_prompt: &str, api_format: &str) -> serde_json::Valu
 
 // ===== Model Resolution =====
 
+/// Model ids from the last `GET /v1/models` refresh, newest first.
+static LIVE_ANTHROPIC_MODELS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Record the Anthropic models the account can use, newest first. Tier names
+/// then resolve to the newest live model of that tier.
+pub fn set_anthropic_models(ids: Vec<String>) {
+    if let Ok(mut models) = LIVE_ANTHROPIC_MODELS.write() {
+        *models = ids;
+    }
+}
+
 fn resolve_anthropic_model(model: &str) -> String {
     // If model already looks like a full model ID (contains dashes), pass through directly
     if model.starts_with("claude-") || model.contains('/') {
         return model.to_string();
     }
+    let live = LIVE_ANTHROPIC_MODELS.read().ok().and_then(|models| {
+        models.iter().find(|id| id.contains(&format!("-{}-", model))).cloned()
+    });
+    if let Some(id) = live {
+        return id;
+    }
     // Map tier names to latest model IDs
     match model {
         "opus" => "claude-opus-4-20250514".to_string(),
@@ -6,6 +6,8 @@ const SAAS_KEYWORDS: &[&str] = &["saas", "subscription", "platform", "dashboard"
 const ECOMMERCE_KEYWORDS: &[&str] = &["ecommerce", "shop", "store", "marketplace", "payment"];
 const DEVTOOL_KEYWORDS: &[&str] = &["developer", "devtool", "api", "sdk", "cli", "code"];
 const AI_KEYWORDS: &[&str] = &["ai", "ml", "machine learning", "gpt", "llm", "neural"];
+const RESEARCH_KEYWORDS: &[&str] = &["research", "study", "paper", "lab", "survey", "literature"];
+const CONTENT_KEYWORDS: &[&str] = &["content", "blog", "newsletter", "podcast", "video", "publishing", "media"];
 
 // Minimum roles every company needs
 const MINIMUM_ROLES: &[&str] = &["ceo", "fullstack", "devops"];
@@ -79,6 +81,10 @@ pub fn analyze_seed(prompt: &str) -> SeedAnalysis {
         "ecommerce"
     } else if DEVTOOL_KEYWORDS.iter().any(|k| lower.contains(k)) {
         "devtool"
+    } else if RESEARCH_KEYWORDS.iter().any(|k| lower.contains(k)) {
+        "research"
+    } else if CONTENT_KEYWORDS.iter().any(|k| lower.contains(k)) {
+        "content"
     } else if AI_KEYWORDS.iter().any(|k| lower.contains(k)) {
         "ai"
     } else {
@@ -142,6 +148,82 @@ pub fn analyze_seed(prompt: &str) -> SeedAnalysis {
     }
 }
 
+/// System prompt asking a model to analyze a seed prompt as structured JSON.
+pub const SEED_ANALYSIS_PROMPT: &str = r#"You analyze startup ideas to staff an autonomous AI company.
+Respond with ONLY a JSON object, no prose, matching:
+{"domain": string, "audience": string, "complexity": "simple" | "medium" | "complex", "features": [string], "suggested_roles": [string]}
+- domain: short lowercase category (e.g. saas, ecommerce, devtool, ai, research, content, fintech, health, gaming, education)
+- features: short lowercase capability names (e.g. authentication, payments, analytics)
+- suggested_roles: chosen from ceo, fullstack, devops, critic, product, ui, qa, marketing, operations, sales, cfo, research"#;
+
+/// Parse a model's JSON answer to `SEED_ANALYSIS_PROMPT`. Code fences and
+/// surrounding prose are tolerated; unknown roles are dropped and the
+/// minimum roles are always included.
+pub fn parse_seed_analysis(text: &str) -> Option<SeedAnalysis> {
+    let start = text.find('{')?;
+    let end = text.rfind('}')?;
+    if end < start {
+        return None;
+    }
+    let value: serde_json::Value = serde_json::from_str(&text[start..=end]).ok()?;
+
+    let string_field = |key: &str| -> Option<String> {
+        value.get(key)?.as_str().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty())
+    };
+    let list_field = |key: &str| -> Vec<String> {
+        value
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|i| i.as_str())
+                    .map(|i| i.trim().to_lowercase())
+                    .filter(|i| !i.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let domain = string_field("domain")?;
+    let audience = string_field("audience").unwrap_or_else(|| "general users".to_string());
+    let complexity = match string_field("complexity").as_deref() {
+        Some("simple") => Complexity::Simple,
+        Some("complex") => Complexity::Complex,
+        _ => Complexity::Medium,
+    };
+
+    let mut features: Vec<String> = Vec::new();
+    for feature in list_field("features") {
+        if !features.contains(&feature) {
+            features.push(feature);
+        }
+    }
+
+    let persona_map = role_to_persona();
+    let mut roles: Vec<String> = MINIMUM_ROLES.iter().map(|r| r.to_string()).collect();
+    roles.extend(
+        list_field("suggested_roles")
+            .into_iter()
+            .filter(|r| persona_map.contains_key(r.as_str())),
+    );
+    roles.sort();
+    roles.dedup();
+    if roles.len() == MINIMUM_ROLES.len() {
+        roles = select_roles(&domain, &complexity);
+    }
+    let team_size = roles.len();
+
+    Some(SeedAnalysis {
+        domain,
+        audience,
+        complexity,
+        features,
+        suggested_roles: roles,
+        team_size,
+    })
+}
+
 fn select_roles(_domain: &str, complexity: &Complexity) -> Vec<String> {
     let mut roles: Vec<String> = MINIMUM_ROLES.iter().map(|r| r.to_string()).collect();
 
@@ -167,13 +249,144 @@ fn select_roles(_domain: &str, complexity: &Complexity) -> Vec<String> {
     roles
 }
 
-pub fn build_config(prompt: &str) -> FactoryConfig {
+struct WorkflowTemplate {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    chain: &'static [&'static str],
+    convergence_cycles: u32,
+    /// Only offered for this seed domain (None = every domain)
+    domain: Option<&'static str>,
+    /// Only offered for complex projects
+    complex_only: bool,
+}
+
+const WORKFLOW_TEMPLATES: &[WorkflowTemplate] = &[
+    // Generic baseline
+    WorkflowTemplate {
+        id: "pricing-monetization",
+        name: "Pricing & Monetization",
+        description: "End-to-end pricing strategy workflow",
+        chain: &["research", "cfo", "product", "marketing", "critic", "cfo"],
+        convergence_cycles: 2,
+        domain: None,
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "product-launch",
+        name: "Product Launch",
+        description: "Coordinated product launch workflow",
+        chain: &["marketing", "research", "sales", "marketing", "devops", "ceo"],
+        convergence_cycles: 2,
+        domain: None,
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "weekly-review",
+        name: "Weekly Review",
+        description: "Weekly strategic review cycle",
+        chain: &["research", "cfo", "marketing", "qa", "ceo", "critic"],
+        convergence_cycles: 1,
+        domain: None,
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "architecture-review",
+        name: "Architecture Review",
+        description: "Design and scalability review before major builds",
+        chain: &["fullstack", "devops", "qa", "critic", "ceo"],
+        convergence_cycles: 2,
+        domain: None,
+        complex_only: true,
+    },
+    // Domain-specific
+    WorkflowTemplate {
+        id: "churn-reduction",
+        name: "Churn Reduction",
+        description: "Find why subscribers leave and ship retention fixes",
+        chain: &["research", "product", "fullstack", "marketing", "ceo"],
+        convergence_cycles: 2,
+        domain: Some("saas"),
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "checkout-optimization",
+        name: "Checkout Optimization",
+        description: "Reduce cart abandonment across the purchase funnel",
+        chain: &["product", "ui", "fullstack", "qa", "cfo"],
+        convergence_cycles: 2,
+        domain: Some("ecommerce"),
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "developer-onboarding",
+        name: "Developer Onboarding",
+        description: "Docs, quickstarts and SDK ergonomics for first-run success",
+        chain: &["product", "fullstack", "devops", "marketing"],
+        convergence_cycles: 2,
+        domain: Some("devtool"),
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "model-evaluation",
+        name: "Model Evaluation",
+        description: "Benchmark model quality, cost and latency before rollout",
+        chain: &["research", "fullstack", "qa", "critic", "ceo"],
+        convergence_cycles: 2,
+        domain: Some("ai"),
+        complex_only: false,
+    },
+    WorkflowTemplate {
+        id: "compliance-review",
+        name: "Compliance Review",
+        description: "Check regulatory and data-handling obligations before launch",
+        chain: &["research", "cfo", "fullstack", "critic", "ceo"],
+        convergence_cycles: 1,
+        domain: Some("fintech"),
+        complex_only: false,
+    },
+];
+
+/// Pick the workflows matching the seed's domain and complexity whose chain
+/// roles all exist on the team.
+fn select_workflows(analysis: &SeedAnalysis) -> Vec<WorkflowConfig> {
+    WORKFLOW_TEMPLATES
+        .iter()
+        .filter(|t| t.domain.is_none_or(|d| d == analysis.domain))
+        .filter(|t| !t.complex_only || analysis.complexity == Complexity::Complex)
+        .filter(|t| t.chain.iter().all(|r| analysis.suggested_roles.iter().any(|s| s == r)))
+        .map(|t| WorkflowConfig {
+            id: t.id.to_string(),
+            name: t.name.to_string(),
+            description: t.description.to_string(),
+            chain: t.chain.iter().map(|s| s.to_string()).collect(),
+            convergence_cycles: t.convergence_cycles,
+        })
+        .collect()
+}
+
+/// Starter consensus template for a seed domain.
+pub fn consensus_template_for_domain(domain: &str) -> &'static str {
+    match domain {
+        "research" | "science" | "academic" => "research",
+        "content" | "media" | "publishing" | "education" => "content",
+        _ => "product",
+    }
+}
+
+/// Build a company config from a seed prompt. `persona_overrides` maps
+/// role -> persona id and takes precedence over the built-in mapping.
+pub fn build_config(prompt: &str, persona_overrides: &HashMap<String, String>) -> FactoryConfig {
     let analysis = analyze_seed(prompt);
     let persona_map = role_to_persona();
 
     // Build agents
     let agents: Vec<AgentConfig> = analysis.suggested_roles.iter().map(|role| {
-        let persona_id = persona_map.get(role.as_str()).unwrap_or(&"generic");
+        let persona_id = persona_overrides
+            .get(role)
+            .map(|id| id.as_str())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| persona_map.get(role.as_str()).copied().unwrap_or("generic"));
         AgentConfig {
             role: role.clone(),
             persona: PersonaRef {
@@ -184,47 +397,11 @@ pub fn build_config(prompt: &str) -> FactoryConfig {
             model: role_to_model(role),
             layer: role_to_layer(role),
             decides: Vec::new(),
+            enabled: true,
         }
     }).collect();
 
-    // Build default workflows
-    let all_roles: Vec<&str> = analysis.suggested_roles.iter().map(|s| s.as_str()).collect();
-
-    let mut workflows = Vec::new();
-
-    // Only include workflows where all chain roles exist
-    let pricing_chain = vec!["research", "cfo", "product", "marketing", "critic", "cfo"];
-    if pricing_chain.iter().all(|r| all_roles.contains(r)) {
-        workflows.push(WorkflowConfig {
-            id: "pricing-monetization".to_string(),
-            name: "Pricing & Monetization".to_string(),
-            description: "End-to-end pricing strategy workflow".to_string(),
-            chain: pricing_chain.iter().map(|s| s.to_string()).collect(),
-            convergence_cycles: 2,
-        });
-    }
-
-    let launch_chain = vec!["marketing", "research", "sales", "marketing", "devops", "ceo"];
-    if launch_chain.iter().all(|r| all_roles.contains(r)) {
-        workflows.push(WorkflowConfig {
-            id: "product-launch".to_string(),
-            name: "Product Launch".to_string(),
-            description: "Coordinated product launch workflow".to_string(),
-            chain: launch_chain.iter().map(|s| s.to_string()).collect(),
-            convergence_cycles: 2,
-        });
-    }
-
-    let review_chain = vec!["research", "cfo", "marketing", "qa", "ceo", "critic"];
-    if review_chain.iter().all(|r| all_roles.contains(r)) {
-        workflows.push(WorkflowConfig {
-            id: "weekly-review".to_string(),
-            name: "Weekly Review".to_string(),
-            description: "Weekly strategic review cycle".to_string(),
-            chain: review_chain.iter().map(|s| s.to_string()).collect(),
-            convergence_cycles: 1,
-        });
-    }
+    let workflows = select_workflows(&analysis);
 
     // Sanitize company name from seed
     let name = format!(
@@ -241,6 +418,8 @@ pub fn build_config(prompt: &str) -> FactoryConfig {
             mission: format!("Build and ship a profitable saas product: {}", prompt),
             description: format!("Domain: {}. Target: {}. Complexity: {:?}.", analysis.domain, analysis.audience, analysis.complexity),
             seed_prompt: prompt.to_string(),
+            template: consensus_template_for_domain(&analysis.domain).to_string(),
+            global_preamble: String::new(),
         },
         org: OrgConfig { agents },
         workflows,
@@ -258,7 +437,15 @@ pub fn build_config(prompt: &str) -> FactoryConfig {
             },
             loop_interval: 30,
             cycle_timeout: 1800,
+            request_timeout: 300,
             max_consecutive_errors: 5,
+            memory_window: 5,
+            memory_entry_chars: 2000,
+            strict_consensus: true,
+            enable_prompt_cache: false,
+            conversation_turns: 2,
+            conversation_message_chars: 4000,
+            required_consensus_sections: default_consensus_sections(),
         },
         guardrails: GuardrailConfig {
             forbidden: vec![
@@ -271,6 +458,7 @@ pub fn build_config(prompt: &str) -> FactoryConfig {
             ],
             workspace: "projects/".to_string(),
             require_critic_review: true,
+            block_unsafe_consensus: false,
         },
     }
 }
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use crate::models::ModelPricing;
+
+// ===== Token Estimation =====
+
+/// Rough token estimate for prompt text (~4 characters per token).
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32).div_ceil(4)
+}
+
+// ===== Model Pricing =====
+
+/// User pricing from `AppSettings::model_pricing`, keyed by lowercased model id.
+static PRICING_OVERRIDES: RwLock<Option<HashMap<String, ModelPricing>>> = RwLock::new(None);
+
+/// Record the configured pricing table. Called whenever settings are loaded or saved.
+pub fn set_pricing_overrides(pricing: &HashMap<String, ModelPricing>) {
+    if let Ok(mut overrides) = PRICING_OVERRIDES.write() {
+        *overrides = Some(pricing.iter().map(|(k, v)| (k.to_lowercase(), *v)).collect());
+    }
+}
+
+/// Configured rates for a model: an exact id match, else the longest
+/// configured id contained in it (so dated ids match their base name).
+fn override_rates(lower: &str) -> Option<(f64, f64)> {
+    let overrides = PRICING_OVERRIDES.read().ok()?;
+    let overrides = overrides.as_ref()?;
+    overrides
+        .get(lower)
+        .or_else(|| {
+            overrides
+                .iter()
+                .filter(|(id, _)| !id.is_empty() && lower.contains(id.as_str()))
+                .max_by_key(|(id, _)| id.len())
+                .map(|(_, p)| p)
+        })
+        .map(|p| (p.input_per_mtok, p.output_per_mtok))
+}
+
+/// Built-in per-million-token rates as (model id fragment, input USD, output USD).
+/// Matched by substring against the lowercased model id, first match wins,
+/// so more specific fragments must come before broader ones.
+const MODEL_RATES: &[(&str, f64, f64)] = &[
+    ("opus", 15.0, 75.0),
+    ("sonnet", 3.0, 15.0),
+    ("haiku", 0.8, 4.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("o4-mini", 1.1, 4.4),
+    ("o3", 2.0, 8.0),
+    ("deepseek-reasoner", 0.55, 2.19),
+    ("deepseek-r1", 0.55, 2.19),
+    ("deepseek", 0.27, 1.1),
+    ("gemini-2.5-pro", 1.25, 10.0),
+    ("gemini-2.5-flash", 0.3, 2.5),
+    ("llama-3.3-70b", 0.59, 0.79),
+    ("llama-3.1-8b", 0.05, 0.08),
+    ("mistral-large", 2.0, 6.0),
+    ("mistral-small", 0.2, 0.6),
+];
+
+/// Fallback rate when a model is unknown (Sonnet-class pricing).
+const DEFAULT_RATES: (f64, f64) = (3.0, 15.0);
+
+/// Look up (input, output) USD per million tokens for a model id or tier name,
+/// preferring the user's pricing table over the built-in rates.
+pub fn model_rates(model: &str) -> (f64, f64) {
+    let lower = model.to_lowercase();
+    if let Some(rates) = override_rates(&lower) {
+        return rates;
+    }
+    MODEL_RATES
+        .iter()
+        .find(|(fragment, _, _)| lower.contains(fragment))
+        .map(|(_, input, output)| (*input, *output))
+        .unwrap_or(DEFAULT_RATES)
+}
+
+/// Cost in USD of a single call with the given token counts.
+pub fn cost_usd(model: &str, input_tokens: u32, output_tokens: u32) -> f64 {
+    let (input_rate, output_rate) = model_rates(model);
+    (input_tokens as f64 * input_rate + output_tokens as f64 * output_rate) / 1_000_000.0
+}
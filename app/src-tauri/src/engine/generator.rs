@@ -2,97 +2,108 @@ use std::fs;
 use std::path::Path;
 use crate::models::*;
 
+/// Tracks files written by `generate_all`. In preserve mode, existing files
+/// whose content differs from the generated output are left untouched.
+struct OutputFiles {
+    mode: GenerateMode,
+    created: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl OutputFiles {
+    fn write(&mut self, path: &Path, content: &str) -> Result<(), String> {
+        if self.mode == GenerateMode::Preserve && path.exists() {
+            let identical = fs::read(path).map(|existing| existing == content.as_bytes()).unwrap_or(false);
+            if !identical {
+                self.skipped.push(path.display().to_string());
+                return Ok(());
+            }
+        }
+        fs::write(path, content).map_err(|e| format!("Write error: {}", e))?;
+        self.created.push(path.display().to_string());
+        Ok(())
+    }
+}
+
 pub fn generate_all(
     config: &FactoryConfig,
     output_dir: &Path,
     _templates_dir: &Path,
+    mode: GenerateMode,
 ) -> Result<GenerateResult, String> {
-    let mut files_created = Vec::new();
+    let mut out = OutputFiles {
+        mode,
+        created: Vec::new(),
+        skipped: Vec::new(),
+    };
 
     // Create directory structure
-    let dirs = [
-        output_dir.join(".claude"),
-        output_dir.join(".claude/agents"),
-        output_dir.join("memories"),
-        output_dir.join("docs"),
-        output_dir.join("projects"),
-        output_dir.join("logs"),
-        output_dir.join("scripts"),
-    ];
-    for dir in &dirs {
-        fs::create_dir_all(dir).map_err(|e| format!("Failed to create dir: {}", e))?;
-    }
-
-    // Create doc dirs for each agent
-    for agent in &config.org.agents {
-        let doc_dir = output_dir.join(format!("docs/{}", agent.role));
-        fs::create_dir_all(&doc_dir).map_err(|e| format!("Failed to create doc dir: {}", e))?;
+    for dir in project_dirs(config) {
+        fs::create_dir_all(output_dir.join(dir)).map_err(|e| format!("Failed to create dir: {}", e))?;
     }
 
     // 1. Generate company.yaml
     let yaml_content = serde_yaml::to_string(config)
         .map_err(|e| format!("YAML serialize error: {}", e))?;
     let config_path = output_dir.join("company.yaml");
-    fs::write(&config_path, &yaml_content).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(config_path.display().to_string());
+    out.write(&config_path, &yaml_content)?;
 
     // 2. Generate CLAUDE.md
     let claude_md = generate_claude_md(config);
     let claude_path = output_dir.join("CLAUDE.md");
-    fs::write(&claude_path, &claude_md).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(claude_path.display().to_string());
+    out.write(&claude_path, &claude_md)?;
 
     // 3. Generate agent files
     for agent in &config.org.agents {
         let agent_md = generate_agent_md(agent, config);
         let path = output_dir.join(format!(".claude/agents/{}-{}.md", agent.role, agent.persona.id));
-        fs::write(&path, &agent_md).map_err(|e| format!("Write error: {}", e))?;
-        files_created.push(path.display().to_string());
+        out.write(&path, &agent_md)?;
     }
 
     // 4. Generate consensus.md
     let consensus = generate_consensus_md(config);
     let consensus_path = output_dir.join("memories/consensus.md");
-    fs::write(&consensus_path, &consensus).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(consensus_path.display().to_string());
+    out.write(&consensus_path, &consensus)?;
 
     // 5. Generate .claude/settings.json
     let settings = generate_settings_json(config);
     let settings_path = output_dir.join(".claude/settings.json");
-    fs::write(
-        &settings_path,
-        serde_json::to_string_pretty(&settings).unwrap(),
-    ).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(settings_path.display().to_string());
+    out.write(&settings_path, &serde_json::to_string_pretty(&settings).unwrap())?;
 
     // 6. Generate workflow docs
     for workflow in &config.workflows {
         let wf_md = generate_workflow_md(workflow);
         let path = output_dir.join(format!("docs/workflow-{}.md", workflow.id));
-        fs::write(&path, &wf_md).map_err(|e| format!("Write error: {}", e))?;
-        files_created.push(path.display().to_string());
+        out.write(&path, &wf_md)?;
     }
 
     // 7. Generate auto-loop script
     let loop_script = generate_loop_script(config);
     let script_path = output_dir.join("scripts/auto-loop.sh");
-    fs::write(&script_path, &loop_script).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(script_path.display().to_string());
+    out.write(&script_path, &loop_script)?;
+
+    let loop_script_ps1 = generate_loop_script_ps1(config);
+    let ps1_path = output_dir.join("scripts/auto-loop.ps1");
+    out.write(&ps1_path, &loop_script_ps1)?;
 
     // 8. Initialize state files
-    let state_content = "current_cycle=0\ntotal_cycles=0\nconsecutive_errors=0\nstatus=stopped\n";
     let state_path = output_dir.join(".loop.state");
-    fs::write(&state_path, state_content).map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(state_path.display().to_string());
+    out.write(&state_path, INITIAL_LOOP_STATE)?;
 
     let history_path = output_dir.join(".cycle_history.json");
-    fs::write(&history_path, "[]").map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(history_path.display().to_string());
+    out.write(&history_path, "[]")?;
 
     // 9. Create empty log file
     let log_path = output_dir.join("logs/auto-loop.log");
-    fs::write(&log_path, "").map_err(|e| format!("Write error: {}", e))?;
-    files_created.push(log_path.display().to_string());
+    out.write(&log_path, "")?;
+
+    // 10. Generate README.md and .gitignore
+    let readme = generate_readme(config);
+    let readme_path = output_dir.join("README.md");
+    out.write(&readme_path, &readme)?;
+
+    let gitignore_path = output_dir.join(".gitignore");
+    out.write(&gitignore_path, GITIGNORE)?;
 
     let unique_skills: std::collections::HashSet<_> = config.org.agents.iter()
         .flat_map(|a| &a.skills)
@@ -100,13 +111,66 @@ pub fn generate_all(
 
     Ok(GenerateResult {
         output_dir: output_dir.display().to_string(),
-        files_created,
+        files_created: out.created,
+        files_skipped: out.skipped,
         agent_count: config.org.agents.len(),
         skill_count: unique_skills.len(),
         workflow_count: config.workflows.len(),
     })
 }
 
+const INITIAL_LOOP_STATE: &str = "current_cycle=0\ntotal_cycles=0\nconsecutive_errors=0\nstatus=stopped\n";
+
+/// Standard project directories, relative to the project root.
+fn project_dirs(config: &FactoryConfig) -> Vec<String> {
+    let mut dirs: Vec<String> = [".claude", ".claude/agents", "memories", "docs", "projects", "logs", "scripts"]
+        .iter()
+        .map(|d| d.to_string())
+        .collect();
+    dirs.extend(config.org.agents.iter().map(|a| format!("docs/{}", a.role)));
+    dirs
+}
+
+/// Recreate missing standard directories and core runtime files (agent
+/// files, consensus, settings, state, history, log) from `config`. Existing
+/// files are never touched. Returns the relative paths that were created.
+pub fn repair_project(config: &FactoryConfig, output_dir: &Path) -> Result<Vec<String>, String> {
+    let mut repaired = Vec::new();
+
+    for dir in project_dirs(config) {
+        let path = output_dir.join(&dir);
+        if !path.is_dir() {
+            fs::create_dir_all(&path).map_err(|e| format!("Failed to create {}: {}", dir, e))?;
+            repaired.push(format!("{}/", dir));
+        }
+    }
+
+    let mut files: Vec<(String, String)> = config.org.agents.iter()
+        .map(|agent| (
+            format!(".claude/agents/{}-{}.md", agent.role, agent.persona.id),
+            generate_agent_md(agent, config),
+        ))
+        .collect();
+    files.push(("memories/consensus.md".to_string(), generate_consensus_md(config)));
+    files.push((
+        ".claude/settings.json".to_string(),
+        serde_json::to_string_pretty(&generate_settings_json(config)).unwrap_or_default(),
+    ));
+    files.push((".loop.state".to_string(), INITIAL_LOOP_STATE.to_string()));
+    files.push((".cycle_history.json".to_string(), "[]".to_string()));
+    files.push(("logs/auto-loop.log".to_string(), String::new()));
+
+    for (rel, content) in files {
+        let path = output_dir.join(&rel);
+        if !path.exists() {
+            fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", rel, e))?;
+            repaired.push(rel);
+        }
+    }
+
+    Ok(repaired)
+}
+
 fn generate_claude_md(config: &FactoryConfig) -> String {
     let mut md = String::new();
 
@@ -165,6 +229,66 @@ fn generate_claude_md(config: &FactoryConfig) -> String {
     md
 }
 
+/// Volatile runtime state that shouldn't be committed with the project.
+const GITIGNORE: &str = r#"# OmniHive runtime state
+.loop.state
+.loop.pid
+.loop.lock
+.cycle_history.json
+.runtime_override.json
+.load_balance.json
+logs/
+
+# Backups and rejected updates
+memories/*.bak
+memories/consensus_*.md.bak
+memories/consensus.rejected.md
+
+# Local secrets
+.env
+"#;
+
+fn generate_readme(config: &FactoryConfig) -> String {
+    let mut md = String::new();
+
+    md.push_str(&format!("# {}\n\n", config.company.name));
+    md.push_str(&format!("{}\n\n", config.company.mission));
+    if !config.company.description.is_empty() {
+        md.push_str(&format!("{}\n\n", config.company.description));
+    }
+
+    md.push_str("## Team\n\n");
+    md.push_str("| Role | Persona | Layer | Model |\n");
+    md.push_str("|------|---------|-------|-------|\n");
+    for agent in &config.org.agents {
+        md.push_str(&format!(
+            "| {} | {} | {:?} | {:?} |\n",
+            agent.role, agent.persona.id, agent.layer, agent.model
+        ));
+    }
+    md.push('\n');
+
+    md.push_str("## Running the Loop\n\n");
+    md.push_str("Start the autonomous loop from the OmniHive dashboard, or run the generated script directly:\n\n");
+    md.push_str("```bash\n./scripts/auto-loop.sh\n```\n\n");
+    md.push_str("On Windows:\n\n");
+    md.push_str("```powershell\n./scripts/auto-loop.ps1\n```\n\n");
+    md.push_str(&format!(
+        "Each cycle one agent reads `memories/consensus.md`, acts on it and records its decisions. \
+         Cycles run every {}s; progress is logged to `logs/auto-loop.log`.\n\n",
+        config.runtime.loop_interval
+    ));
+
+    md.push_str("## Layout\n\n");
+    md.push_str("- `company.yaml` — company, team and runtime configuration\n");
+    md.push_str("- `CLAUDE.md` — shared operating rules and guardrails\n");
+    md.push_str("- `.claude/agents/` — one prompt file per agent\n");
+    md.push_str("- `memories/consensus.md` — the shared plan every cycle builds on\n");
+    md.push_str("- `docs/` — workflow docs and per-role output\n");
+
+    md
+}
+
 fn generate_agent_md(agent: &AgentConfig, config: &FactoryConfig) -> String {
     let mut md = String::new();
 
@@ -222,31 +346,55 @@ fn generate_agent_md(agent: &AgentConfig, config: &FactoryConfig) -> String {
     md
 }
 
+/// Starting focus, projects and next action for each consensus template, as
+/// (focus, active projects, next action). Every template shares the section
+/// headings required by the runtime's consensus checks.
+fn consensus_template(template: &str) -> (&'static str, &'static str, &'static str) {
+    match template {
+        "research" => (
+            "Starting up. First cycle should frame the key research questions behind our mission.",
+            "None yet. First cycle will scope the open questions and available sources.",
+            "**Framing Phase**: Each team member proposes the research question they think matters most and how to investigate it.",
+        ),
+        "content" => (
+            "Starting up. First cycle should define our audience and the content pillars that serve our mission.",
+            "None yet. First cycle will draft an editorial calendar.",
+            "**Editorial Phase**: Each team member pitches a content series and the channel it belongs on.",
+        ),
+        _ => (
+            "Starting up. First cycle should brainstorm product ideas aligned with our mission.",
+            "None yet. First cycle will identify opportunities.",
+            "**Brainstorm Phase**: Each team member proposes their best product idea based on our mission.",
+        ),
+    }
+}
+
 fn generate_consensus_md(config: &FactoryConfig) -> String {
+    let (focus, projects, next_action) = consensus_template(&config.company.template);
     format!(
         r#"# Auto Company Consensus
 
 ## Company State
 
-- **Company**: {}
-- **Mission**: {}
+- **Company**: {name}
+- **Mission**: {mission}
 - **Status**: INITIALIZING
 - **Cycle**: 0
 - **Revenue**: $0
 
 ## Current Focus
 
-Starting up. First cycle should brainstorm product ideas aligned with our mission.
+{focus}
 
-Seed direction: {}
+Seed direction: {seed}
 
 ## Active Projects
 
-None yet. First cycle will identify opportunities.
+{projects}
 
 ## Next Action
 
-**Brainstorm Phase**: Each team member proposes their best product idea based on our mission.
+{next_action}
 
 ## Decision Log
 
@@ -254,7 +402,9 @@ None yet. First cycle will identify opportunities.
 |-------|----------|---------|---------|
 | 0 | Company initialized | System | Pending first cycle |
 "#,
-        config.company.name, config.company.mission, config.company.seed_prompt
+        name = config.company.name,
+        mission = config.company.mission,
+        seed = config.company.seed_prompt,
     )
 }
 
@@ -311,12 +461,15 @@ STATE_FILE="$PROJECT_DIR/.loop.state"
 HISTORY_FILE="$PROJECT_DIR/.cycle_history.json"
 LOG_FILE="$PROJECT_DIR/logs/auto-loop.log"
 CONSENSUS="$PROJECT_DIR/memories/consensus.md"
+LOCK_FILE="$PROJECT_DIR/.loop.lock"
 
 ENGINE="${{ENGINE:-claude}}"
 MODEL="${{MODEL:-sonnet}}"
 MAX_ERRORS={}
 LOOP_INTERVAL={}
 CYCLE_TIMEOUT={}
+# Same staleness window the app uses for .loop.lock
+LOCK_STALE_AFTER=$((CYCLE_TIMEOUT + 120))
 
 CYCLE=0
 ERRORS=0
@@ -339,6 +492,30 @@ last_cycle_at=$(date -Iseconds)
 EOF
 }}
 
+write_lock() {{
+    printf 'pid=%s\ntimestamp=%s\n' "$$" "$(date +%s)" > "$LOCK_FILE"
+}}
+
+release_lock() {{
+    if [ -f "$LOCK_FILE" ] && [ "$(sed -n 's/^pid=//p' "$LOCK_FILE")" = "$$" ]; then
+        rm -f "$LOCK_FILE"
+    fi
+}}
+
+# Refuse to run alongside another loop (this script or the app) on the project
+if [ -f "$LOCK_FILE" ]; then
+    LOCK_PID=$(sed -n 's/^pid=//p' "$LOCK_FILE")
+    LOCK_TS=$(sed -n 's/^timestamp=//p' "$LOCK_FILE")
+    LOCK_AGE=$(( $(date +%s) - ${{LOCK_TS:-0}} ))
+    if [ "$LOCK_AGE" -ge 0 ] && [ "$LOCK_AGE" -lt "$LOCK_STALE_AFTER" ]; then
+        echo "Another process (pid ${{LOCK_PID:-?}}) is running a loop on this project; lock refreshed ${{LOCK_AGE}}s ago" >&2
+        exit 1
+    fi
+    log "Removed stale .loop.lock (${{LOCK_AGE}}s old)"
+fi
+write_lock
+trap release_lock EXIT
+
 log "Starting auto-loop for {}"
 log "Engine: $ENGINE | Model: $MODEL | Agents: ${{#AGENTS[@]}}"
 update_state "running"
@@ -359,6 +536,7 @@ while true; do
     fi
 
     STARTED_AT=$(date -Iseconds)
+    write_lock
 
     PROMPT="You are the $CURRENT_AGENT agent. Read memories/consensus.md, perform your role, and update consensus with your findings."
 
@@ -380,7 +558,14 @@ while true; do
     update_state "running"
 
     log "Sleeping $LOOP_INTERVAL seconds..."
-    sleep "$LOOP_INTERVAL"
+    # Sleep in short steps so the lock stays fresh
+    REMAINING=$LOOP_INTERVAL
+    while [ "$REMAINING" -gt 0 ]; do
+        STEP=$(( REMAINING < 30 ? REMAINING : 30 ))
+        sleep "$STEP"
+        REMAINING=$((REMAINING - STEP))
+        write_lock
+    done
 done
 "#,
         config.company.name,
@@ -391,3 +576,160 @@ done
         config.company.name,
     )
 }
+
+fn generate_loop_script_ps1(config: &FactoryConfig) -> String {
+    let agent_roles: Vec<String> = config
+        .org
+        .agents
+        .iter()
+        .map(|a| format!("\"{}\"", a.role))
+        .collect();
+
+    format!(
+        r#"# Auto-loop script for {}
+# Generated by Omnihive
+# Usage: powershell -ExecutionPolicy Bypass -File scripts\auto-loop.ps1
+
+$ErrorActionPreference = "Stop"
+
+$ProjectDir = Split-Path -Parent $PSScriptRoot
+$StateFile = Join-Path $ProjectDir ".loop.state"
+$LogFile = Join-Path $ProjectDir "logs\auto-loop.log"
+$AgentsDir = Join-Path $ProjectDir ".claude\agents"
+$LockFile = Join-Path $ProjectDir ".loop.lock"
+
+$Engine = if ($env:ENGINE) {{ $env:ENGINE }} else {{ "claude" }}
+$Model = if ($env:MODEL) {{ $env:MODEL }} else {{ "sonnet" }}
+$MaxErrors = {}
+$LoopInterval = {}
+$CycleTimeout = {}
+# Same staleness window the app uses for .loop.lock
+$LockStaleAfter = $CycleTimeout + 120
+
+$Errors = 0
+$Agents = @({})
+
+function Write-Log([string]$Message) {{
+    $line = "[{{0}}] {{1}}" -f (Get-Date -Format "yyyy-MM-dd HH:mm:ss"), $Message
+    Add-Content -Path $LogFile -Value $line
+    Write-Host $line
+}}
+
+function Write-Lock {{
+    @("pid=$PID", "timestamp=$([DateTimeOffset]::UtcNow.ToUnixTimeSeconds())") | Set-Content -Path $LockFile
+}}
+
+function Remove-Lock {{
+    if ((Test-Path $LockFile) -and ((Get-Content $LockFile) -contains "pid=$PID")) {{
+        Remove-Item $LockFile -ErrorAction SilentlyContinue
+    }}
+}}
+
+function Update-State([string]$Status) {{
+    @(
+        "current_cycle=$Cycle"
+        "total_cycles=$Cycle"
+        "consecutive_errors=$Errors"
+        "status=$Status"
+        "last_cycle_at=$(Get-Date -Format o)"
+    ) | Set-Content -Path $StateFile
+}}
+
+# Continue numbering from the last recorded cycle
+$Cycle = 0
+if (Test-Path $StateFile) {{
+    foreach ($line in Get-Content $StateFile) {{
+        if ($line -match '^current_cycle=(\d+)$') {{ $Cycle = [int]$Matches[1] }}
+    }}
+}}
+
+# Refuse to run alongside another loop (this script or the app) on the project
+if (Test-Path $LockFile) {{
+    $LockPid = "?"
+    $LockTs = 0
+    foreach ($line in Get-Content $LockFile) {{
+        if ($line -match '^pid=(\d+)$') {{ $LockPid = $Matches[1] }}
+        if ($line -match '^timestamp=(\d+)$') {{ $LockTs = [long]$Matches[1] }}
+    }}
+    $LockAge = [DateTimeOffset]::UtcNow.ToUnixTimeSeconds() - $LockTs
+    if ($LockAge -ge 0 -and $LockAge -lt $LockStaleAfter) {{
+        Write-Host "Another process (pid $LockPid) is running a loop on this project; lock refreshed ${{LockAge}}s ago"
+        exit 1
+    }}
+    Write-Log "Removed stale .loop.lock (${{LockAge}}s old)"
+}}
+Write-Lock
+
+Write-Log "Starting auto-loop for {}"
+Write-Log "Engine: $Engine | Model: $Model | Agents: $($Agents.Count)"
+Update-State "running"
+
+try {{
+    while ($true) {{
+        $Cycle++
+        $CurrentAgent = $Agents[($Cycle - 1) % $Agents.Count]
+        Write-Lock
+
+        Write-Log "=== Cycle ${{Cycle}}: Agent $CurrentAgent ==="
+
+        $AgentFile = Get-ChildItem -Path $AgentsDir -Filter "$CurrentAgent-*.md" -ErrorAction SilentlyContinue | Select-Object -First 1
+        if (-not $AgentFile) {{
+            Write-Log "WARNING: No agent file for $CurrentAgent, skipping"
+            continue
+        }}
+
+        $Prompt = "You are the $CurrentAgent agent. Read memories/consensus.md, perform your role, and update consensus with your findings."
+        $OutFile = [System.IO.Path]::GetTempFileName()
+        $ErrFile = [System.IO.Path]::GetTempFileName()
+
+        $Proc = Start-Process -FilePath $Engine `
+            -ArgumentList @("--print", "--model", $Model, "`"$Prompt`"") `
+            -WorkingDirectory $ProjectDir -NoNewWindow -PassThru `
+            -RedirectStandardOutput $OutFile -RedirectStandardError $ErrFile
+
+        $Succeeded = $false
+        if ($Proc.WaitForExit($CycleTimeout * 1000)) {{
+            $Succeeded = ($Proc.ExitCode -eq 0)
+        }} else {{
+            $Proc.Kill()
+            Write-Log "Cycle $Cycle timed out after $CycleTimeout seconds"
+        }}
+
+        Get-Content $OutFile, $ErrFile | Add-Content -Path $LogFile
+        Remove-Item $OutFile, $ErrFile -ErrorAction SilentlyContinue
+
+        if ($Succeeded) {{
+            $Errors = 0
+            Write-Log "Cycle $Cycle completed successfully"
+        }} else {{
+            $Errors++
+            Write-Log "ERROR: Cycle $Cycle failed (consecutive errors: $Errors)"
+
+            if ($Errors -ge $MaxErrors) {{
+                Write-Log "FATAL: Max consecutive errors reached ($MaxErrors). Stopping."
+                Update-State "error"
+                exit 1
+            }}
+        }}
+
+        Update-State "running"
+
+        Write-Log "Sleeping $LoopInterval seconds..."
+        # Sleep in short steps so the lock stays fresh
+        for ($Remaining = $LoopInterval; $Remaining -gt 0; $Remaining -= 30) {{
+            Start-Sleep -Seconds ([Math]::Min($Remaining, 30))
+            Write-Lock
+        }}
+    }}
+}} finally {{
+    Remove-Lock
+}}
+"#,
+        config.company.name,
+        config.runtime.max_consecutive_errors,
+        config.runtime.loop_interval,
+        config.runtime.cycle_timeout,
+        agent_roles.join(", "),
+        config.company.name,
+    )
+}
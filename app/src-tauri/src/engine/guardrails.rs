@@ -1,5 +1,5 @@
 use regex::Regex;
-use crate::models::GuardrailConfig;
+use crate::models::{FactoryConfig, GuardrailConfig};
 
 const DEFAULT_FORBIDDEN: &[&str] = &[
     "gh repo delete",
@@ -47,6 +47,54 @@ pub fn check_command_safety(command: &str, config: &GuardrailConfig) -> Result<(
     Ok(())
 }
 
+/// Extract shell commands an agent proposes: lines inside ```bash/```sh/```shell
+/// fences, and the text after a `Run:` prefix.
+pub fn extract_shell_commands(text: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut in_shell_block = false;
+    let mut in_other_block = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if let Some(lang) = trimmed.strip_prefix("```") {
+            if in_shell_block || in_other_block {
+                in_shell_block = false;
+                in_other_block = false;
+            } else if matches!(lang.trim(), "bash" | "sh" | "shell" | "zsh" | "console") {
+                in_shell_block = true;
+            } else {
+                in_other_block = true;
+            }
+            continue;
+        }
+
+        if in_shell_block {
+            let cmd = trimmed.trim_start_matches("$ ");
+            if !cmd.is_empty() && !cmd.starts_with('#') {
+                commands.push(cmd.to_string());
+            }
+        } else if !in_other_block {
+            if let Some(rest) = trimmed.strip_prefix("Run:") {
+                let cmd = rest.trim().trim_matches('`');
+                if !cmd.is_empty() {
+                    commands.push(cmd.to_string());
+                }
+            }
+        }
+    }
+
+    commands
+}
+
+/// Run every command found in an agent response through `check_command_safety`.
+/// Returns one message per violating command.
+pub fn check_response_commands(text: &str, config: &GuardrailConfig) -> Vec<String> {
+    extract_shell_commands(text)
+        .iter()
+        .filter_map(|cmd| check_command_safety(cmd, config).err().map(|e| format!("{} (in `{}`)", e, cmd)))
+        .collect()
+}
+
 pub fn validate_config_guardrails(config: &GuardrailConfig) -> Vec<String> {
     let mut warnings = Vec::new();
 
@@ -64,3 +112,27 @@ pub fn validate_config_guardrails(config: &GuardrailConfig) -> Vec<String> {
 
     warnings
 }
+
+pub fn validate_config_workflows(config: &FactoryConfig) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    for workflow in &config.workflows {
+        for role in &workflow.chain {
+            if !config.org.agents.iter().any(|a| &a.role == role) {
+                warnings.push(format!(
+                    "Workflow '{}' references role '{}' which is not defined in the org.",
+                    workflow.name, role
+                ));
+            }
+        }
+
+        if workflow.convergence_cycles == 0 {
+            warnings.push(format!(
+                "Workflow '{}' has convergence_cycles set to 0 and will never converge.",
+                workflow.name
+            ));
+        }
+    }
+
+    warnings
+}
@@ -0,0 +1,59 @@
+use std::sync::RwLock;
+
+/// Proxy from `AppSettings::proxy_url`; empty = fall back to env vars.
+static PROXY_URL: RwLock<String> = RwLock::new(String::new());
+
+/// Record the configured proxy. Called whenever settings are loaded or saved.
+pub fn set_proxy_url(url: &str) {
+    if let Ok(mut proxy) = PROXY_URL.write() {
+        *proxy = url.trim().to_string();
+    }
+}
+
+/// The configured proxy, else `HTTPS_PROXY` / `HTTP_PROXY` (either case).
+fn effective_proxy() -> Option<String> {
+    let configured = PROXY_URL.read().map(|p| p.clone()).unwrap_or_default();
+    if !configured.is_empty() {
+        return Some(configured);
+    }
+    ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+}
+
+/// Check that a proxy URL can be used. The URL itself is left out of the
+/// error since it may carry credentials.
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    ureq::Proxy::new(url.trim())
+        .map(|_| ())
+        .map_err(|e| format!("Invalid proxy URL: {}", e))
+}
+
+/// An agent builder for requests to `url`, routed through the proxy when one
+/// is configured. Loopback hosts (e.g. a local Ollama) always go direct.
+pub fn agent_builder(url: &str) -> Result<ureq::AgentBuilder, String> {
+    let builder = ureq::AgentBuilder::new();
+    if is_loopback(url) {
+        return Ok(builder);
+    }
+    match effective_proxy() {
+        Some(proxy) => {
+            let proxy = ureq::Proxy::new(proxy.trim()).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            Ok(builder.proxy(proxy))
+        }
+        None => Ok(builder),
+    }
+}
+
+fn is_loopback(url: &str) -> bool {
+    let host = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or(host);
+    host.starts_with("localhost") || host.starts_with("127.") || host.starts_with("[::1]")
+}
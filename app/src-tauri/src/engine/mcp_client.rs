@@ -0,0 +1,428 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use crate::models::McpToolInfo;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// ===== Stdio Transport =====
+
+/// A running stdio MCP server with a completed `initialize` handshake.
+/// The child process is killed when the session is dropped.
+pub struct StdioSession {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+    timeout: Duration,
+    pub server_name: String,
+}
+
+impl StdioSession {
+    /// Spawn `command` and perform the `initialize` / `notifications/initialized` handshake.
+    pub fn connect(mut command: Command, timeout: Duration) -> Result<Self, String> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to start MCP server: {}", e))?;
+
+        let stdin = child.stdin.take().ok_or("Failed to open MCP server stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open MCP server stdout")?;
+
+        // Read stdout on a helper thread so requests can time out
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(l) => {
+                        if tx.send(l).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let mut session = StdioSession {
+            child,
+            stdin,
+            lines,
+            next_id: 1,
+            timeout,
+            server_name: String::new(),
+        };
+
+        let result = session.request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "omnihive", "version": env!("CARGO_PKG_VERSION")}
+            }),
+        )?;
+        session.server_name = result
+            .pointer("/serverInfo/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        session.notify("notifications/initialized")?;
+        Ok(session)
+    }
+
+    /// List all tools, following `nextCursor` pagination.
+    pub fn list_tools(&mut self) -> Result<Vec<McpToolInfo>, String> {
+        list_all_tools(|params| self.request("tools/list", params))
+    }
+
+    fn send(&mut self, message: &serde_json::Value) -> Result<(), String> {
+        let line = format!("{}\n", message);
+        self.stdin
+            .write_all(line.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("Failed to write to MCP server: {}", e))
+    }
+
+    fn notify(&mut self, method: &str) -> Result<(), String> {
+        self.send(&serde_json::json!({"jsonrpc": "2.0", "method": method}))
+    }
+
+    /// Send a JSON-RPC request and wait for the response with the same id,
+    /// skipping server notifications and non-JSON output.
+    fn request(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = self.lines.recv_timeout(remaining).map_err(|e| match e {
+                mpsc::RecvTimeoutError::Timeout => {
+                    format!("MCP server did not respond to '{}' within {}s", method, self.timeout.as_secs())
+                }
+                mpsc::RecvTimeoutError::Disconnected => {
+                    format!("MCP server exited before responding to '{}'", method)
+                }
+            })?;
+
+            if let Some(result) = match_response(&line, id, method) {
+                return result;
+            }
+        }
+    }
+}
+
+impl Drop for StdioSession {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// ===== HTTP Transports =====
+
+/// A remote MCP server with a completed `initialize` handshake, reached over
+/// `sse` (replies on a long-lived event stream, requests POSTed to the
+/// endpoint it announces) or `streamable-http` (one POST per request, replied
+/// to with JSON or a short event stream).
+pub struct HttpSession {
+    agent: ureq::Agent,
+    url: String,
+    headers: Vec<(String, String)>,
+    transport: HttpTransport,
+    next_id: u64,
+    timeout: Duration,
+    pub server_name: String,
+}
+
+enum HttpTransport {
+    Sse { endpoint: String, events: Receiver<SseEvent> },
+    /// `Mcp-Session-Id` is echoed back once the server assigns one
+    Streamable { session_id: Option<String> },
+}
+
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+impl HttpSession {
+    /// Open the transport for `server_type` and perform the handshake.
+    /// `headers` are sent with every request (e.g. `Authorization`).
+    pub fn connect(
+        url: &str,
+        server_type: &str,
+        headers: Vec<(String, String)>,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let agent = super::http::agent_builder(url)?
+            .timeout_connect(timeout)
+            .timeout_read(timeout)
+            .build();
+
+        let transport = match server_type {
+            "sse" => {
+                let mut req = agent.get(url).set("Accept", "text/event-stream");
+                for (name, value) in &headers {
+                    req = req.set(name, value);
+                }
+                let resp = req.call().map_err(http_error)?;
+                if !resp.content_type().contains("text/event-stream") {
+                    return Err(format!(
+                        "Endpoint responded with '{}' instead of an event stream",
+                        resp.content_type()
+                    ));
+                }
+
+                // Read the stream on a helper thread so requests can time out
+                let (tx, events) = mpsc::channel();
+                let reader = BufReader::new(resp.into_reader());
+                std::thread::spawn(move || {
+                    read_sse_events(reader, |event| tx.send(event).is_ok());
+                });
+
+                // The first event names the URL to POST messages to
+                let endpoint = loop {
+                    let event = events.recv_timeout(timeout).map_err(|_| {
+                        format!("MCP server did not announce a message endpoint within {}s", timeout.as_secs())
+                    })?;
+                    if event.event == "endpoint" {
+                        break resolve_endpoint(url, event.data.trim());
+                    }
+                };
+                HttpTransport::Sse { endpoint, events }
+            }
+            "streamable-http" => HttpTransport::Streamable { session_id: None },
+            other => return Err(format!("Unsupported MCP HTTP transport: {}", other)),
+        };
+
+        let mut session = HttpSession {
+            agent,
+            url: url.to_string(),
+            headers,
+            transport,
+            next_id: 1,
+            timeout,
+            server_name: String::new(),
+        };
+
+        let result = session.request(
+            "initialize",
+            serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "omnihive", "version": env!("CARGO_PKG_VERSION")}
+            }),
+        )?;
+        session.server_name = result
+            .pointer("/serverInfo/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+
+        session.post(&serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))?;
+        Ok(session)
+    }
+
+    /// List all tools, following `nextCursor` pagination.
+    pub fn list_tools(&mut self) -> Result<Vec<McpToolInfo>, String> {
+        list_all_tools(|params| self.request("tools/list", params))
+    }
+
+    /// POST a JSON-RPC message to the transport's message URL.
+    fn post(&mut self, message: &serde_json::Value) -> Result<ureq::Response, String> {
+        let url = match &self.transport {
+            HttpTransport::Sse { endpoint, .. } => endpoint.clone(),
+            HttpTransport::Streamable { .. } => self.url.clone(),
+        };
+        let mut req = self
+            .agent
+            .post(&url)
+            .set("Accept", "application/json, text/event-stream");
+        for (name, value) in &self.headers {
+            req = req.set(name, value);
+        }
+        if let HttpTransport::Streamable { session_id: Some(id) } = &self.transport {
+            req = req.set("Mcp-Session-Id", id);
+        }
+
+        let resp = req.send_json(message.clone()).map_err(http_error)?;
+        if let HttpTransport::Streamable { session_id } = &mut self.transport {
+            if let Some(id) = resp.header("Mcp-Session-Id") {
+                *session_id = Some(id.to_string());
+            }
+        }
+        Ok(resp)
+    }
+
+    /// Send a JSON-RPC request and wait for the response with the same id.
+    fn request(&mut self, method: &str, params: serde_json::Value) -> Result<serde_json::Value, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let resp = self.post(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+
+        match &self.transport {
+            HttpTransport::Sse { events, .. } => {
+                let deadline = Instant::now() + self.timeout;
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    let event = events.recv_timeout(remaining).map_err(|e| match e {
+                        mpsc::RecvTimeoutError::Timeout => {
+                            format!("MCP server did not respond to '{}' within {}s", method, self.timeout.as_secs())
+                        }
+                        mpsc::RecvTimeoutError::Disconnected => {
+                            format!("MCP event stream closed before responding to '{}'", method)
+                        }
+                    })?;
+                    if let Some(result) = match_response(&event.data, id, method) {
+                        return result;
+                    }
+                }
+            }
+            HttpTransport::Streamable { .. } => {
+                if resp.content_type().contains("text/event-stream") {
+                    let mut found = None;
+                    read_sse_events(BufReader::new(resp.into_reader()), |event| {
+                        found = match_response(&event.data, id, method);
+                        found.is_none()
+                    });
+                    found.unwrap_or_else(|| Err(format!("MCP server closed the stream before responding to '{}'", method)))
+                } else {
+                    let body = resp
+                        .into_string()
+                        .map_err(|e| format!("Failed to read MCP response: {}", e))?;
+                    match_response(&body, id, method)
+                        .unwrap_or_else(|| Err(format!("MCP server sent no response to '{}'", method)))
+                }
+            }
+        }
+    }
+}
+
+/// Parse a `text/event-stream`, calling `on_event` for each complete event
+/// until it returns false or the stream ends.
+fn read_sse_events(reader: impl BufRead, mut on_event: impl FnMut(SseEvent) -> bool) {
+    let mut event = String::new();
+    let mut data: Vec<String> = Vec::new();
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        if line.is_empty() {
+            if !data.is_empty() {
+                let complete = SseEvent {
+                    event: if event.is_empty() { "message".to_string() } else { std::mem::take(&mut event) },
+                    data: data.join("\n"),
+                };
+                data.clear();
+                if !on_event(complete) {
+                    return;
+                }
+            }
+            event.clear();
+        } else if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+        }
+    }
+}
+
+/// Resolve the endpoint announced by an SSE server against the stream URL.
+fn resolve_endpoint(base: &str, endpoint: &str) -> String {
+    if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        return endpoint.to_string();
+    }
+    let host_start = base.find("://").map(|i| i + 3).unwrap_or(0);
+    let origin_end = base[host_start..].find('/').map(|i| host_start + i).unwrap_or(base.len());
+    format!("{}/{}", &base[..origin_end], endpoint.trim_start_matches('/'))
+}
+
+fn http_error(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(code, resp) => {
+            let body = resp.into_string().unwrap_or_default();
+            format!("MCP server error (HTTP {}): {}", code, body.chars().take(300).collect::<String>())
+        }
+        e => format!("Failed to reach MCP server: {}", e),
+    }
+}
+
+// ===== Helpers =====
+
+/// Page through `tools/list`, following `nextCursor`.
+fn list_all_tools(
+    mut request: impl FnMut(serde_json::Value) -> Result<serde_json::Value, String>,
+) -> Result<Vec<McpToolInfo>, String> {
+    let mut tools = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let params = match &cursor {
+            Some(c) => serde_json::json!({"cursor": c}),
+            None => serde_json::json!({}),
+        };
+        let result = request(params)?;
+        tools.extend(parse_tools(&result));
+
+        cursor = result
+            .get("nextCursor")
+            .and_then(|v| v.as_str())
+            .filter(|c| !c.is_empty())
+            .map(|c| c.to_string());
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    Ok(tools)
+}
+
+/// If `raw` is the JSON-RPC response to request `id`, its result or error.
+/// Notifications, other ids and non-JSON output yield `None`.
+fn match_response(raw: &str, id: u64, method: &str) -> Option<Result<serde_json::Value, String>> {
+    let msg = serde_json::from_str::<serde_json::Value>(raw).ok()?;
+    if msg.get("id").and_then(|v| v.as_u64()) != Some(id) {
+        return None;
+    }
+    if let Some(err) = msg.get("error") {
+        let message = err.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+        return Some(Err(format!("MCP '{}' failed: {}", method, message)));
+    }
+    Some(Ok(msg.get("result").cloned().unwrap_or(serde_json::Value::Null)))
+}
+
+/// Extract tool names and descriptions from a `tools/list` result.
+pub fn parse_tools(result: &serde_json::Value) -> Vec<McpToolInfo> {
+    result
+        .get("tools")
+        .and_then(|t| t.as_array())
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|t| {
+                    let name = t.get("name")?.as_str()?.to_string();
+                    let description = t
+                        .get("description")
+                        .and_then(|d| d.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    Some(McpToolInfo { name, description })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -16,9 +16,11 @@ pub fn read_consensus(project_dir: &Path) -> Result<ConsensusState, String> {
     let mut current_focus = String::new();
     let mut next_action = String::new();
     let active_projects: Vec<String> = Vec::new();
+    let mut decisions: Vec<DecisionEntry> = Vec::new();
 
     let mut in_focus = false;
     let mut in_next = false;
+    let mut in_decisions = false;
 
     for line in content.lines() {
         let trimmed = line.trim();
@@ -42,12 +44,23 @@ pub fn read_consensus(project_dir: &Path) -> Result<ConsensusState, String> {
         } else if trimmed == "## Current Focus" {
             in_focus = true;
             in_next = false;
+            in_decisions = false;
         } else if trimmed == "## Next Action" {
             in_next = true;
             in_focus = false;
+            in_decisions = false;
+        } else if trimmed == "## Decision Log" {
+            in_decisions = true;
+            in_focus = false;
+            in_next = false;
         } else if trimmed.starts_with("## ") {
             in_focus = false;
             in_next = false;
+            in_decisions = false;
+        } else if in_decisions && trimmed.starts_with('|') {
+            if let Some(entry) = parse_decision_row(trimmed) {
+                decisions.push(entry);
+            }
         } else if in_focus && !trimmed.is_empty() {
             if !current_focus.is_empty() {
                 current_focus.push(' ');
@@ -70,11 +83,36 @@ pub fn read_consensus(project_dir: &Path) -> Result<ConsensusState, String> {
         current_focus,
         active_projects,
         next_action,
+        decisions,
         raw_content: content,
     })
 }
 
-pub fn update_consensus(project_dir: &Path, content: &str) -> Result<(), String> {
+/// Parse one Decision Log table row. The header and separator rows (whose
+/// first cell is not a cycle number) are skipped; missing trailing cells
+/// are left empty.
+fn parse_decision_row(line: &str) -> Option<DecisionEntry> {
+    let cells = parse_table_row(line);
+    let cycle = cells.first()?.parse().ok()?;
+    let cell = |i: usize| cells.get(i).cloned().unwrap_or_default();
+    Some(DecisionEntry {
+        cycle,
+        decision: cell(1),
+        made_by: cell(2),
+        outcome: cell(3),
+    })
+}
+
+/// Write a new consensus, keeping the previous one in `consensus.md.bak` and
+/// snapshotting the new one into `consensus_history/` for `cycle`.
+/// Content failing `validate_consensus` against the `required` sections is
+/// rejected unless `force` is set; the validation problems are returned either way.
+pub fn update_consensus(project_dir: &Path, content: &str, cycle: u32, force: bool, required: &[String]) -> Result<Vec<String>, String> {
+    let problems = validate_consensus(content, required);
+    if !problems.is_empty() && !force {
+        return Err(format!("Invalid consensus: {}", problems.join("; ")));
+    }
+
     let path = project_dir.join("memories/consensus.md");
 
     // Backup first
@@ -87,7 +125,180 @@ pub fn update_consensus(project_dir: &Path, content: &str) -> Result<(), String>
     fs::write(&path, content)
         .map_err(|e| format!("Failed to write consensus: {}", e))?;
 
-    Ok(())
+    snapshot_consensus(project_dir, content, cycle)?;
+    Ok(problems)
+}
+
+/// Default for `RuntimeConfig::required_consensus_sections`.
+pub const REQUIRED_CONSENSUS_SECTIONS: &[&str] = &["## Company State", "## Current Focus", "## Decision Log"];
+
+/// Check that the `required` sections are present and that the Decision Log,
+/// if there is one, holds a well-formed table. Returns one message per problem.
+pub fn validate_consensus(content: &str, required: &[String]) -> Vec<String> {
+    let mut problems: Vec<String> = required
+        .iter()
+        .map(|section| section.trim())
+        .filter(|section| !section.is_empty() && !content.lines().any(|l| l.trim() == *section))
+        .map(|section| format!("Missing section '{}'", section))
+        .collect();
+
+    let rows: Vec<Vec<String>> = content
+        .lines()
+        .skip_while(|l| l.trim() != "## Decision Log")
+        .skip(1)
+        .take_while(|l| !l.trim_start().starts_with("## "))
+        .filter(|l| l.trim_start().starts_with('|'))
+        .map(parse_table_row)
+        .collect();
+
+    if content.lines().any(|l| l.trim() == "## Decision Log") {
+        match rows.split_first() {
+            None => problems.push("Decision Log has no table".to_string()),
+            Some((header, rest)) => {
+                if !rest.first().is_some_and(|r| is_table_separator(r)) {
+                    problems.push("Decision Log table is missing its separator row".to_string());
+                }
+                for (i, row) in rest.iter().enumerate().skip(1) {
+                    if row.len() != header.len() {
+                        problems.push(format!(
+                            "Decision Log row {} has {} columns, expected {}",
+                            i, row.len(), header.len()
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+// ===== Consensus History =====
+
+fn consensus_history_dir(project_dir: &Path) -> std::path::PathBuf {
+    project_dir.join("memories/consensus_history")
+}
+
+/// Save `content` as `consensus_history/{timestamp}_{cycle}.md`.
+fn snapshot_consensus(project_dir: &Path, content: &str, cycle: u32) -> Result<(), String> {
+    let dir = consensus_history_dir(project_dir);
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create consensus history dir: {}", e))?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+    fs::write(dir.join(format!("{}_{}.md", timestamp, cycle)), content)
+        .map_err(|e| format!("Failed to snapshot consensus: {}", e))
+}
+
+/// List consensus snapshots, newest first.
+pub fn list_consensus_versions(project_dir: &Path) -> Vec<ConsensusVersion> {
+    let entries = match fs::read_dir(consensus_history_dir(project_dir)) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut versions: Vec<ConsensusVersion> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("md") {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            // {YYYYmmdd}_{HHMMSS}_{cycle}
+            let mut parts = id.splitn(3, '_');
+            let stamp = format!("{}_{}", parts.next()?, parts.next()?);
+            let cycle = parts.next()?.parse().ok()?;
+            let created_at = chrono::NaiveDateTime::parse_from_str(&stamp, "%Y%m%d_%H%M%S")
+                .ok()?
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
+            let size_bytes = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            Some(ConsensusVersion { id, cycle, created_at, size_bytes })
+        })
+        .collect();
+
+    versions.sort_by(|a, b| b.id.cmp(&a.id));
+    versions
+}
+
+/// Copy a snapshot back to `consensus.md`. The current consensus is backed up
+/// and the restore itself is recorded as a new snapshot.
+pub fn restore_consensus_version(project_dir: &Path, version_id: &str, required: &[String]) -> Result<(), String> {
+    let version = list_consensus_versions(project_dir)
+        .into_iter()
+        .find(|v| v.id == version_id)
+        .ok_or_else(|| format!("Consensus version '{}' not found", version_id))?;
+
+    let content = fs::read_to_string(consensus_history_dir(project_dir).join(format!("{}.md", version.id)))
+        .map_err(|e| format!("Failed to read consensus version: {}", e))?;
+    update_consensus(project_dir, &content, version.cycle, true, required).map(|_| ())
+}
+
+/// Largest old x new line product `diff_consensus` will compare.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Line diff between the newest snapshots taken for two cycles.
+pub fn diff_consensus(project_dir: &Path, from_cycle: u32, to_cycle: u32) -> Result<Vec<DiffLine>, String> {
+    let versions = list_consensus_versions(project_dir);
+    let read_cycle = |cycle: u32| -> Result<String, String> {
+        // Versions are newest first, so this is the latest snapshot for the cycle
+        let version = versions
+            .iter()
+            .find(|v| v.cycle == cycle)
+            .ok_or_else(|| format!("No consensus snapshot for cycle {}", cycle))?;
+        fs::read_to_string(consensus_history_dir(project_dir).join(format!("{}.md", version.id)))
+            .map_err(|e| format!("Failed to read consensus version: {}", e))
+    };
+    let old = read_cycle(from_cycle)?;
+    let new = read_cycle(to_cycle)?;
+    diff_lines(&old, &new)
+}
+
+/// LCS-based line diff.
+pub fn diff_lines(old: &str, new: &str) -> Result<Vec<DiffLine>, String> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELLS {
+        return Err(format!("Documents too large to diff ({} x {} lines)", a.len(), b.len()));
+    }
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let line = |kind: &str, text: &str, old_line: Option<usize>, new_line: Option<usize>| DiffLine {
+        kind: kind.to_string(),
+        text: text.to_string(),
+        old_line,
+        new_line,
+    };
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            diff.push(line("unchanged", a[i], Some(i + 1), Some(j + 1)));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(line("removed", a[i], Some(i + 1), None));
+            i += 1;
+        } else {
+            diff.push(line("added", b[j], None, Some(j + 1)));
+            j += 1;
+        }
+    }
+    diff.extend(a[i..].iter().enumerate().map(|(k, t)| line("removed", t, Some(i + k + 1), None)));
+    diff.extend(b[j..].iter().enumerate().map(|(k, t)| line("added", t, None, Some(j + k + 1))));
+    Ok(diff)
 }
 
 pub fn backup_consensus(project_dir: &Path) -> Result<String, String> {
@@ -100,3 +311,97 @@ pub fn backup_consensus(project_dir: &Path) -> Result<String, String> {
 
     Ok(backup_path.display().to_string())
 }
+
+// ===== Markdown Lint =====
+
+/// Split a markdown table row into trimmed cell values.
+pub fn parse_table_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    inner.split('|').map(|c| c.trim().to_string()).collect()
+}
+
+fn is_table_separator(cells: &[String]) -> bool {
+    !cells.is_empty()
+        && cells.iter().all(|c| {
+            let c = c.trim_matches(':');
+            !c.is_empty() && c.chars().all(|ch| ch == '-')
+        })
+}
+
+/// Check consensus markdown for rendering problems: unbalanced code fences,
+/// tables with inconsistent column counts, and skipped heading levels.
+/// Line numbers are 1-based.
+pub fn lint_markdown(content: &str) -> Vec<ConsensusLintIssue> {
+    let mut issues = Vec::new();
+    let mut fence_open: Option<usize> = None;
+    let mut table: Option<(usize, usize)> = None; // (start line, header columns)
+    let mut last_heading_level = 0usize;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence_open = match fence_open {
+                Some(_) => None,
+                None => Some(line_no),
+            };
+            table = None;
+            continue;
+        }
+        if fence_open.is_some() {
+            continue;
+        }
+
+        if trimmed.starts_with('|') {
+            let cells = parse_table_row(trimmed);
+            match table {
+                None => table = Some((line_no, cells.len())),
+                Some((start, columns)) => {
+                    if line_no == start + 1 && !is_table_separator(&cells) {
+                        issues.push(ConsensusLintIssue {
+                            line: line_no,
+                            kind: "table".to_string(),
+                            message: "Table header is not followed by a separator row".to_string(),
+                        });
+                    }
+                    if cells.len() != columns {
+                        issues.push(ConsensusLintIssue {
+                            line: line_no,
+                            kind: "table".to_string(),
+                            message: format!("Row has {} columns, header has {}", cells.len(), columns),
+                        });
+                    }
+                }
+            }
+            continue;
+        }
+        table = None;
+
+        if trimmed.starts_with('#') {
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            if level <= 6 && trimmed[level..].starts_with(' ') {
+                if last_heading_level > 0 && level > last_heading_level + 1 {
+                    issues.push(ConsensusLintIssue {
+                        line: line_no,
+                        kind: "heading".to_string(),
+                        message: format!("Heading jumps from level {} to {}", last_heading_level, level),
+                    });
+                }
+                last_heading_level = level;
+            }
+        }
+    }
+
+    if let Some(start) = fence_open {
+        issues.push(ConsensusLintIssue {
+            line: start,
+            kind: "code_fence".to_string(),
+            message: "Code fence is never closed".to_string(),
+        });
+    }
+
+    issues
+}
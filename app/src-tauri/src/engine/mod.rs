@@ -1,5 +1,9 @@
 pub mod api_client;
 pub mod bootstrap;
+pub mod cost;
 pub mod generator;
+pub mod http;
+pub mod mcp_client;
 pub mod memory;
 pub mod guardrails;
+pub mod webhook;
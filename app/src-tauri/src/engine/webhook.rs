@@ -0,0 +1,50 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Target from `AppSettings::webhook_url`; empty = notifications off.
+static WEBHOOK_URL: RwLock<String> = RwLock::new(String::new());
+
+/// Record the configured webhook. Called whenever settings are loaded or saved.
+pub fn set_webhook_url(url: &str) {
+    if let Ok(mut webhook) = WEBHOOK_URL.write() {
+        *webhook = url.trim().to_string();
+    }
+}
+
+pub fn webhook_url() -> Option<String> {
+    let url = WEBHOOK_URL.read().map(|u| u.clone()).unwrap_or_default();
+    (!url.is_empty()).then_some(url)
+}
+
+pub fn validate_webhook_url(url: &str) -> Result<(), String> {
+    let url = url.trim();
+    if url.starts_with("https://") || url.starts_with("http://") {
+        Ok(())
+    } else {
+        Err("Webhook URL must start with http:// or https://".to_string())
+    }
+}
+
+/// POST a loop event to `url`. The payload carries both structured fields and
+/// a `text`/`content` line so Slack and Discord incoming webhooks render it.
+pub fn post_event(url: &str, project: &str, event_type: &str, cycle: u32, summary: &str) -> Result<(), String> {
+    let text = format!("[{}] {} (cycle {}): {}", project, event_type, cycle, summary);
+    let payload = serde_json::json!({
+        "project": project,
+        "event_type": event_type,
+        "cycle": cycle,
+        "summary": summary,
+        "timestamp": chrono::Local::now().format("%+").to_string(),
+        "text": text,
+        "content": text,
+    });
+
+    let agent = super::http::agent_builder(url)?
+        .timeout(Duration::from_secs(10))
+        .build();
+    match agent.post(url).send_json(payload) {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, _)) => Err(format!("Webhook returned HTTP {}", code)),
+        Err(e) => Err(format!("Webhook request failed: {}", e)),
+    }
+}
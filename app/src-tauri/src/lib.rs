@@ -13,9 +13,14 @@ use commands::provider_presets as provider_presets_cmd;
 use commands::mcp as mcp_cmd;
 use commands::skill_manager as skill_mgr_cmd;
 use commands::repo_manager as repo_mgr_cmd;
+use commands::support as support_cmd;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Load settings once up front so the configured proxy applies from the first request
+    let _ = settings_cmd::load_settings();
+    settings_cmd::load_cached_anthropic_models();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
@@ -24,26 +29,58 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Bootstrap commands
             bootstrap_cmd::analyze_seed,
+            bootstrap_cmd::analyze_seed_llm,
+            bootstrap_cmd::preview_bootstrap,
             bootstrap_cmd::bootstrap,
             bootstrap_cmd::generate,
+            bootstrap_cmd::repair_project,
             bootstrap_cmd::validate_config,
+            bootstrap_cmd::validate_config_file,
             bootstrap_cmd::save_config,
             // Memory commands
             memory_cmd::read_consensus,
             memory_cmd::update_consensus,
             memory_cmd::backup_consensus,
+            memory_cmd::lint_consensus,
+            memory_cmd::list_consensus_versions,
+            memory_cmd::restore_consensus_version,
+            memory_cmd::diff_consensus,
             // Runtime commands
             runtime_cmd::start_loop,
+            runtime_cmd::resume_crashed_loop,
+            runtime_cmd::run_single_cycle,
             runtime_cmd::stop_loop,
+            runtime_cmd::stop_all_loops,
+            runtime_cmd::list_running_loops,
             runtime_cmd::resolve_runtime_config,
             runtime_cmd::get_status,
             runtime_cmd::get_cycle_history,
             runtime_cmd::get_agent_memory,
+            runtime_cmd::get_agent_memory_full,
+            runtime_cmd::compact_agent_memory,
             runtime_cmd::get_handoff_note,
+            runtime_cmd::get_handoff_history,
             runtime_cmd::tail_log,
+            runtime_cmd::tail_log_structured,
             runtime_cmd::test_api_call,
+            runtime_cmd::preview_cycle_prompt,
+            runtime_cmd::analyze_skill_cost,
+            runtime_cmd::estimate_loop_cost,
+            runtime_cmd::reorder_agents,
+            runtime_cmd::set_agent_enabled,
+            runtime_cmd::preview_role_skills,
+            runtime_cmd::get_role_skills,
+            runtime_cmd::save_role_skills,
+            runtime_cmd::list_project_files,
+            runtime_cmd::get_skill_wishlist,
+            runtime_cmd::follow_log,
+            runtime_cmd::stop_following_log,
+            runtime_cmd::add_project_note,
+            runtime_cmd::list_project_notes,
             runtime_cmd::get_project_runtime_override,
             runtime_cmd::set_project_runtime_override,
+            runtime_cmd::get_load_balance,
+            runtime_cmd::set_load_balance,
             runtime_cmd::get_project_events,
             runtime_cmd::auto_select_provider,
             // Library commands
@@ -53,18 +90,37 @@ pub fn run() {
             library_cmd::list_projects,
             library_cmd::get_project,
             library_cmd::delete_project,
+            library_cmd::archive_project,
+            library_cmd::list_archived_projects,
+            library_cmd::restore_project,
+            library_cmd::export_project,
+            library_cmd::import_project,
             library_cmd::get_skill_content,
+            library_cmd::search_library,
+            library_cmd::save_workflow,
+            library_cmd::delete_workflow,
             library_cmd::toggle_library_item,
+            library_cmd::set_persona_enabled,
+            library_cmd::set_skill_enabled,
             library_cmd::get_library_state,
+            library_cmd::validate_library,
             // Settings commands
             settings_cmd::load_settings,
             settings_cmd::save_settings,
+            settings_cmd::get_model_pricing,
+            settings_cmd::set_model_pricing,
+            settings_cmd::get_default_provider_ids,
+            settings_cmd::set_default_provider,
             settings_cmd::add_provider,
             settings_cmd::update_provider,
             settings_cmd::remove_provider,
             settings_cmd::test_provider,
+            settings_cmd::list_provider_models,
+            settings_cmd::refresh_anthropic_models,
+            settings_cmd::refresh_provider_health,
             // Provider detection commands
             provider_detect_cmd::detect_providers,
+            provider_detect_cmd::detect_providers_verbose,
             provider_detect_cmd::export_providers,
             provider_detect_cmd::import_providers,
             // Provider presets commands
@@ -72,6 +128,8 @@ pub fn run() {
             // System commands
             system_cmd::detect_system,
             system_cmd::install_tool,
+            system_cmd::uninstall_tool,
+            system_cmd::update_tool,
             system_cmd::check_engine,
             // MCP commands
             mcp_cmd::list_mcp_servers,
@@ -79,6 +137,8 @@ pub fn run() {
             mcp_cmd::update_mcp_server,
             mcp_cmd::remove_mcp_server,
             mcp_cmd::get_mcp_presets,
+            mcp_cmd::discover_mcp_tools,
+            mcp_cmd::test_mcp_server,
             // Skill manager commands
             skill_mgr_cmd::scan_local_skills,
             skill_mgr_cmd::add_custom_skill,
@@ -100,6 +160,9 @@ pub fn run() {
             repo_mgr_cmd::browse_repo,
             repo_mgr_cmd::browse_repo_skills,
             repo_mgr_cmd::install_repo_skill,
+            repo_mgr_cmd::install_all_repo_skills,
+            // Support commands
+            support_cmd::create_support_bundle,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
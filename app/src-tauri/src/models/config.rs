@@ -35,6 +35,15 @@ pub enum Complexity {
     Complex,
 }
 
+/// How `generate` treats files that already exist in the project.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GenerateMode {
+    #[default]
+    Overwrite,
+    Preserve,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum ProjectStatus {
@@ -64,6 +73,9 @@ pub struct AgentConfig {
     pub layer: AgentLayer,
     #[serde(default)]
     pub decides: Vec<String>,
+    /// Disabled agents stay in the config but sit out loop cycles.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,16 +111,51 @@ pub struct RuntimeConfig {
     pub budget: BudgetConfig,
     #[serde(default = "default_loop_interval")]
     pub loop_interval: u32,
+    /// Wall-clock budget for a whole cycle, in seconds
     #[serde(default = "default_cycle_timeout")]
     pub cycle_timeout: u32,
+    /// Socket read timeout for each API request, in seconds
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout: u32,
     #[serde(default = "default_max_errors")]
     pub max_consecutive_errors: u32,
+    /// Number of recent reflection entries from an agent's MEMORY.md put in its prompt
+    #[serde(default = "default_memory_window")]
+    pub memory_window: usize,
+    /// Per-entry character budget for those reflections
+    #[serde(default = "default_memory_entry_chars")]
+    pub memory_entry_chars: usize,
+    /// Reject consensus updates missing required sections (lenient mode applies them with a warning)
+    #[serde(default = "default_true")]
+    pub strict_consensus: bool,
+    /// Send the agent system prompt as an Anthropic prompt-cache breakpoint
+    #[serde(default)]
+    pub enable_prompt_cache: bool,
+    /// Recent user/assistant exchanges replayed to each agent (0 = single-turn)
+    #[serde(default = "default_conversation_turns")]
+    pub conversation_turns: usize,
+    /// Character cap on each stored conversation message; the user turn
+    /// carries the whole consensus, so this bounds conversation.json
+    #[serde(default = "default_conversation_message_chars")]
+    pub conversation_message_chars: usize,
+    /// Headers an agent's consensus update must contain to be accepted
+    #[serde(default = "default_consensus_sections")]
+    pub required_consensus_sections: Vec<String>,
 }
 
 fn default_failover() -> String { "auto".to_string() }
 fn default_loop_interval() -> u32 { 30 }
 fn default_cycle_timeout() -> u32 { 1800 }
+fn default_request_timeout() -> u32 { 300 }
 fn default_max_errors() -> u32 { 5 }
+fn default_memory_window() -> usize { 5 }
+fn default_memory_entry_chars() -> usize { 2000 }
+fn default_true() -> bool { true }
+fn default_conversation_turns() -> usize { 2 }
+fn default_conversation_message_chars() -> usize { 4000 }
+pub fn default_consensus_sections() -> Vec<String> {
+    crate::engine::memory::REQUIRED_CONSENSUS_SECTIONS.iter().map(|s| s.to_string()).collect()
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardrailConfig {
@@ -117,6 +164,9 @@ pub struct GuardrailConfig {
     pub workspace: String,
     #[serde(default)]
     pub require_critic_review: bool,
+    /// Skip the consensus update when an agent response contains unsafe commands
+    #[serde(default)]
+    pub block_unsafe_consensus: bool,
 }
 
 fn default_workspace() -> String { "projects/".to_string() }
@@ -128,6 +178,14 @@ pub struct CompanyConfig {
     #[serde(default)]
     pub description: String,
     pub seed_prompt: String,
+    /// Starter consensus layout: "product", "research" or "content".
+    /// Empty = "product".
+    #[serde(default)]
+    pub template: String,
+    /// Shared instructions (tone, compliance, ...) prepended to every agent's
+    /// system prompt
+    #[serde(default)]
+    pub global_preamble: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,6 +202,15 @@ pub struct FactoryConfig {
     pub guardrails: GuardrailConfig,
 }
 
+/// Result of checking a company.yaml. `errors` prevent the file from loading;
+/// `warnings` are advisory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigValidation {
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
 // ===== Seed Analysis =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +236,15 @@ pub struct RuntimeStatus {
     pub uptime_seconds: u64,
 }
 
+/// One active loop, as reported by `list_running_loops`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningLoopInfo {
+    pub project_dir: String,
+    pub current_cycle: u32,
+    pub last_cycle_at: Option<String>,
+    pub uptime_seconds: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CycleResult {
     pub cycle_number: u32,
@@ -179,6 +255,12 @@ pub struct CycleResult {
     pub outcome: String,
     pub files_changed: Vec<String>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub input_tokens: u32,
+    #[serde(default)]
+    pub output_tokens: u32,
+    #[serde(default)]
+    pub cost_usd: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -191,9 +273,53 @@ pub struct ConsensusState {
     pub current_focus: String,
     pub active_projects: Vec<String>,
     pub next_action: String,
+    #[serde(default)]
+    pub decisions: Vec<DecisionEntry>,
     pub raw_content: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionEntry {
+    pub cycle: u32,
+    pub decision: String,
+    pub made_by: String,
+    pub outcome: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusVersion {
+    /// File stem under memories/consensus_history, `{timestamp}_{cycle}`
+    pub id: String,
+    pub cycle: u32,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
+/// One line of a consensus diff. `kind` is "added", "removed" or "unchanged";
+/// line numbers are 1-based and absent on the side the line doesn't exist in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: String,
+    pub text: String,
+    pub old_line: Option<usize>,
+    pub new_line: Option<usize>,
+}
+
+/// One reflection from an agent's `MEMORY.md`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryEntry {
+    pub cycle: u32,
+    pub timestamp: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsensusLintIssue {
+    pub line: usize,
+    pub kind: String,
+    pub message: String,
+}
+
 // ===== Library =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -243,6 +369,23 @@ pub struct WorkflowInfo {
     pub tags: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibrarySearchResult {
+    pub kind: String, // "persona" | "skill" | "workflow"
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub score: u32,
+}
+
+/// A library file that failed to parse and is therefore missing from listings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryValidationIssue {
+    pub kind: String, // "persona" | "skill" | "workflow"
+    pub file_path: String,
+    pub error: String,
+}
+
 // ===== Project =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -264,6 +407,9 @@ pub struct Project {
 pub struct GenerateResult {
     pub output_dir: String,
     pub files_created: Vec<String>,
+    /// Existing files left untouched in preserve mode
+    #[serde(default)]
+    pub files_skipped: Vec<String>,
     pub agent_count: usize,
     pub skill_count: usize,
     pub workflow_count: usize,
@@ -291,10 +437,27 @@ pub struct RepoItem {
     pub description: String,
 }
 
+/// Outcome of installing every skill in a repository.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallAllResult {
+    pub installed: Vec<SkillInfo>,
+    pub failed: Vec<SkillInstallFailure>,
+}
+
+/// A repo skill that failed to install, by its path in the repo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillInstallFailure {
+    pub path: String,
+    pub error: String,
+}
+
 // ===== App Settings =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
+    /// Settings file format version; older files are migrated on load
+    #[serde(default)]
+    pub schema_version: u32,
     pub default_engine: String,
     pub default_model: String,
     pub max_daily_budget: f64,
@@ -311,10 +474,43 @@ pub struct AppSettings {
     pub mcp_servers: Vec<McpServerConfig>,
     #[serde(default)]
     pub skill_repos: Vec<SkillRepo>,
+    /// Path to a library checkout (personas/skills/workflows); empty = auto-detect
+    #[serde(default)]
+    pub library_dir: String,
+    /// Role -> persona id used by bootstrap instead of the built-in mapping
+    #[serde(default)]
+    pub role_persona_overrides: std::collections::HashMap<String, String>,
+    /// Personal access token for GitHub API calls (skill repos); empty = anonymous
+    #[serde(default)]
+    pub github_token: String,
+    /// How long cached GitHub browse results are reused, in seconds
+    #[serde(default = "default_github_cache_ttl")]
+    pub github_cache_ttl_secs: u64,
+    /// HTTP(S) proxy for all outbound requests; empty = use HTTPS_PROXY / HTTP_PROXY
+    #[serde(default)]
+    pub proxy_url: String,
+    /// Model id -> USD rates, checked before the built-in table
+    #[serde(default)]
+    pub model_pricing: std::collections::HashMap<String, ModelPricing>,
+    /// Incoming-webhook URL (Slack, Discord, ...) notified of notable loop
+    /// events; empty = disabled
+    #[serde(default)]
+    pub webhook_url: String,
+    /// provider_type -> id of the provider to use when several match
+    #[serde(default)]
+    pub default_provider_ids: std::collections::HashMap<String, String>,
+}
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ModelPricing {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
 }
 
 fn default_language() -> String { "en".to_string() }
 fn default_theme() -> String { "obsidian".to_string() }
+fn default_github_cache_ttl() -> u64 { 600 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiProvider {
@@ -337,6 +533,12 @@ pub struct AiProvider {
     pub force_stream: bool,
     #[serde(default = "default_api_format")]
     pub api_format: String,
+    /// Include Anthropic `thinking` blocks in the response text
+    #[serde(default)]
+    pub include_thinking: bool,
+    /// Extra top-level request body fields for gateways that need them
+    #[serde(default)]
+    pub extra_body: std::collections::HashMap<String, serde_json::Value>,
 }
 
 fn default_provider_engine() -> String { "claude".to_string() }
@@ -367,7 +569,7 @@ pub struct McpServerConfig {
     pub command: String,             // for stdio: executable command
     pub args: Vec<String>,           // for stdio: command arguments
     pub url: String,                 // for sse/http: server URL
-    pub env: std::collections::HashMap<String, String>,  // environment variables
+    pub env: std::collections::HashMap<String, String>,  // environment variables ("keyring:<name>" = OS keyring entry)
     pub enabled: bool,
     pub tools: Vec<McpToolInfo>,     // discovered tools (cached)
 }
@@ -386,6 +588,11 @@ pub struct ProjectRegistryEntry {
     pub name: String,
     pub output_dir: String,
     pub created_at: String,
+    #[serde(default)]
+    pub archived: bool,
+    /// Where an archived project lived before archiving, for `restore_project`
+    #[serde(default)]
+    pub archived_from: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -403,6 +610,48 @@ pub struct LogEntry {
     pub message: String,
 }
 
+// ===== Skill Wishlist =====
+
+/// A skill agents asked for that the library doesn't have.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillWish {
+    pub skill_id: String,
+    /// Roles that requested it
+    pub requested_by: Vec<String>,
+    pub count: u32,
+    pub first_requested: String,
+    pub last_requested: String,
+    pub last_cycle: u32,
+}
+
+// ===== Project Notes =====
+
+/// A human-written note about a project, kept apart from agent memory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectNote {
+    pub id: String,
+    /// RFC 3339 creation time
+    pub timestamp: String,
+    /// Cycle the note refers to, if any
+    #[serde(default)]
+    pub cycle: Option<u32>,
+    pub text: String,
+}
+
+// ===== Project Files =====
+
+/// A file in a project directory, for the project file browser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    /// Path relative to the project root, with `/` separators
+    pub path: String,
+    /// "agent", "memory", "doc", "log", "config" or "other"
+    pub category: String,
+    pub size: u64,
+    /// RFC 3339 modification time, empty when unavailable
+    pub modified: String,
+}
+
 // ===== Per-Project Runtime Override =====
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -415,6 +664,28 @@ pub struct ProjectRuntimeOverride {
     pub provider_id: Option<String>,
 }
 
+// ===== Per-Project Load Balancing =====
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LoadBalanceConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider_ids: Vec<String>,
+    #[serde(default)]
+    pub usage: std::collections::HashMap<String, ProviderUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ProviderUsage {
+    #[serde(default)]
+    pub cycles: u64,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
 // ===== Project Event (Activity Feed) =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -441,6 +712,13 @@ pub struct DetectedProvider {
     pub suggested_model: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionReport {
+    pub providers: Vec<DetectedProvider>,
+    /// One line per config path / endpoint examined, with its outcome
+    pub checked: Vec<String>,
+}
+
 // ===== System Environment =====
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
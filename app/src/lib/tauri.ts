@@ -39,8 +39,9 @@ export async function analyzeSeed(prompt: string): Promise<SeedAnalysis> {
 export async function bootstrap(
   prompt: string,
   outputDir: string,
+  overwrite = false,
 ): Promise<FactoryConfig> {
-  return invoke("bootstrap", { prompt, outputDir });
+  return invoke("bootstrap", { prompt, outputDir, overwrite });
 }
 
 // ===== Generator Commands =====
@@ -104,8 +105,9 @@ export async function readConsensus(
 export async function updateConsensus(
   projectDir: string,
   content: string,
-): Promise<boolean> {
-  return invoke("update_consensus", { projectDir, content });
+  force = false,
+): Promise<readonly string[]> {
+  return invoke("update_consensus", { projectDir, content, force });
 }
 
 // ===== Library Commands =====
@@ -325,8 +327,8 @@ export async function removeSkillRepo(repoId: string): Promise<AppSettings> {
   return invoke("remove_skill_repo", { repoId });
 }
 
-export async function browseRepo(repoId: string, subpath: string): Promise<readonly RepoItem[]> {
-  return invoke("browse_repo", { repoId, subpath });
+export async function browseRepo(repoId: string, subpath: string, refresh = false): Promise<readonly RepoItem[]> {
+  return invoke("browse_repo", { repoId, subpath, refresh });
 }
 
 export async function browseRepoSkills(repoId: string): Promise<readonly RepoItem[]> {
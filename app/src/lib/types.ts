@@ -61,6 +61,8 @@ export interface CompanyConfig {
   readonly mission: string;
   readonly description: string;
   readonly seed_prompt: string;
+  readonly template?: string;
+  readonly global_preamble?: string;
 }
 
 export interface OrgConfig {
@@ -273,6 +275,8 @@ export interface AiProvider {
   readonly extra_headers?: Record<string, string>;
   readonly force_stream?: boolean;
   readonly api_format?: string;
+  readonly include_thinking?: boolean;
+  readonly extra_body?: Record<string, unknown>;
 }
 
 // ===== Tauri Command Results =====